@@ -1,29 +1,84 @@
-use nannou::wgpu::Texture;
+use std::{
+    sync::{
+        mpsc::{self, Receiver, TrySendError},
+        Arc,
+    },
+    thread,
+};
+
+use nannou::{image::RgbaImage, wgpu::Texture};
 use raytracer::{
-    render::{Camera, Renderer},
+    render::{Camera, Film, Renderer},
     scene::Scene,
 };
 
+/// Number of passes after which the progressive render stops refining the image.
+const MAX_PASSES: u32 = 256;
+
 pub struct Model {
-    pub renderer: Renderer,
-    pub camera: Camera,
-    pub scene: Scene,
+    /// Latest film received from the render thread.
+    pub film: Option<Film>,
     pub texture: Option<Texture>,
+
+    /// Render running in the background, once a scene is loaded.
+    render: Option<Render>,
+}
+
+///
+/// Progressive render running on a worker thread.
+///
+struct Render {
+    renderer: Arc<Renderer>,
+    films: Receiver<Film>,
 }
 
 impl Model {
+    ///
+    /// Create a new `Model`, and start a progressive render of the scene.
+    ///
     pub fn new(renderer: Renderer, camera: Camera, scene: Scene) -> Self {
-        Self {
-            renderer,
-            camera,
-            scene,
+        let mut model = Self::empty();
+        model.load(renderer, camera, scene);
 
+        model
+    }
+
+    ///
+    /// Create a `Model` with no scene loaded. Nothing is rendered until [Model::load] is called.
+    ///
+    pub fn empty() -> Self {
+        Self {
+            film: None,
             texture: None,
+
+            render: None,
         }
     }
 
-    pub fn empty() -> Self {
-        Self::new(Default::default(), Default::default(), Default::default())
+    ///
+    /// Start a progressive render of the scene on a worker thread, so that the UI stays
+    /// responsive.
+    ///
+    /// Any previous render is dropped, and its worker stops after its current pass.
+    ///
+    pub fn load(&mut self, renderer: Renderer, camera: Camera, scene: Scene) {
+        let renderer = Arc::new(renderer);
+        let films = spawn_render(renderer.clone(), camera, scene);
+
+        self.film = None;
+        self.render = Some(Render { renderer, films });
+    }
+
+    ///
+    /// Swap in the latest film rendered by the worker thread, and develop it.
+    ///
+    /// Return `None` if no scene is loaded, or if no pass completed since the last call.
+    ///
+    pub fn poll_image(&mut self) -> Option<RgbaImage> {
+        let render = self.render.as_ref()?;
+
+        self.film = Some(render.films.try_iter().last()?);
+        self.film.as_ref().map(|film| render.renderer.develop(film))
     }
 }
 
@@ -32,3 +87,35 @@ impl Default for Model {
         Self::empty()
     }
 }
+
+///
+/// Spawn the thread running the progressive render passes.
+///
+/// A copy of the film is sent after each pass. At most one copy waits in the channel: passes
+/// completed while the UI hasn't taken it are skipped, except for the last one.
+///
+fn spawn_render(renderer: Arc<Renderer>, camera: Camera, scene: Scene) -> Receiver<Film> {
+    let (sender, receiver) = mpsc::sync_channel(1);
+
+    thread::spawn(move || {
+        let mut progressive = renderer.start_progressive(&camera);
+
+        while progressive.passes() < MAX_PASSES {
+            renderer.render_pass(&mut progressive, &camera, &scene);
+
+            let film = progressive.film().clone();
+            let sent = if progressive.passes() < MAX_PASSES {
+                !matches!(sender.try_send(film), Err(TrySendError::Disconnected(_)))
+            } else {
+                sender.send(film).is_ok()
+            };
+
+            // The UI is gone.
+            if !sent {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
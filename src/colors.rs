@@ -1,6 +1,11 @@
 //!
 //! Color module.
 //!
+//! [Rgba] is the 8 bits per channel color used for display, while [Color] is a floating point
+//! color in linear space, used to accumulate light during rendering.
+//!
+
+use std::ops::{Add, AddAssign, Div, Mul};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Rgba([u8; 4]);
@@ -28,3 +33,113 @@ impl Rgba {
         self.0
     }
 }
+
+impl From<Color> for Rgba {
+    ///
+    /// Convert a linear color to 8 bits, clamping each channel to `[0, 1]`.
+    ///
+    fn from(value: Color) -> Self {
+        let to_u8 = |c: f32| (c.clamp(0., 1.) * 255.).round() as u8;
+
+        Self::new(
+            to_u8(value.r),
+            to_u8(value.g),
+            to_u8(value.b),
+            to_u8(value.a),
+        )
+    }
+}
+
+///
+/// Floating point color, in linear space.
+///
+/// Channels are nominally in `[0, 1]`, but are not clamped, so that light can be accumulated
+/// before being displayed.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn from_rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.)
+    }
+
+    pub const BLACK: Color = Color::from_rgb(0., 0., 0.);
+    pub const WHITE: Color = Color::from_rgb(1., 1., 1.);
+    pub const TRANSPARENT: Color = Color::new(0., 0., 0., 0.);
+
+    pub fn to_rgba(self) -> Rgba {
+        self.into()
+    }
+}
+
+impl From<Rgba> for Color {
+    fn from(value: Rgba) -> Self {
+        let [r, g, b, a] = value.to_array().map(|c| c as f32 / 255.);
+
+        Self::new(r, g, b, a)
+    }
+}
+
+impl Add for Color {
+    type Output = Color;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.r + rhs.r,
+            self.g + rhs.g,
+            self.b + rhs.b,
+            self.a + rhs.a,
+        )
+    }
+}
+
+impl AddAssign for Color {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Mul<f32> for Color {
+    type Output = Color;
+
+    ///
+    /// Scale each channel, alpha included, by a given factor.
+    ///
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self::new(self.r * rhs, self.g * rhs, self.b * rhs, self.a * rhs)
+    }
+}
+
+impl Mul for Color {
+    type Output = Color;
+
+    ///
+    /// Multiply two colors channel by channel, e.g. to filter light by a surface color.
+    ///
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.r * rhs.r,
+            self.g * rhs.g,
+            self.b * rhs.b,
+            self.a * rhs.a,
+        )
+    }
+}
+
+impl Div<f32> for Color {
+    type Output = Color;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Self::new(self.r / rhs, self.g / rhs, self.b / rhs, self.a / rhs)
+    }
+}
@@ -6,7 +6,14 @@ use raytracer::maths::Point;
 use raytracer::scene::objects::Sphere;
 use raytracer::scene::*;
 
-fn update(_: &App, _model: &mut Model, _update: Update) {}
+fn update(app: &App, model: &mut Model, _update: Update) {
+    let Some(img) = model.poll_image() else {
+        return
+    };
+
+    let img = DynamicImage::ImageRgba8(img);
+    model.texture = Some(wgpu::Texture::from_image(app, &img));
+}
 
 fn model(app: &App) -> Model {
     let scene = scene()
@@ -20,36 +27,16 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
 
-    Model {
-        scene,
-        ..Default::default()
-    }
+    Model::new(Default::default(), Default::default(), scene)
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    let (tx, ty) = app.main_window().inner_size_points();
-    let tx = tx as u32;
-    let ty = ty as u32;
-    //let [tx, ty] = frame.texture_size();
-
-    let mut img = DynamicImage::new_rgba8(tx, ty);
-
-    {
-        let rgba_img = img.as_mut_rgba8().unwrap();
-
-        for y in 0..ty {
-            for x in 0..tx {
-                let color = model
-                    .renderer
-                    .render_pixel(x, y, &model.camera, &model.scene);
-                rgba_img.put_pixel(x, y, color.to_array().into());
-            }
-        }
-    }
-
-    let texture = wgpu::Texture::from_image(app, &img);
+    let Some(texture) = &model.texture else {
+        return
+    };
+
     let draw = app.draw();
-    draw.texture(&texture);
+    draw.texture(texture);
     draw.to_frame(app, &frame).unwrap();
 }
 
@@ -1,13 +1,23 @@
 //! Maths primitives and utilities
 
+pub mod aabb;
+pub mod bvh;
+pub mod distribution;
+pub mod microfacet;
+pub mod noise;
 pub mod point;
 pub mod quaternion;
+pub mod sampling;
 pub mod vector;
 
+pub mod polynom;
 pub mod polynom2;
 
+pub use aabb::Aabb;
+pub use bvh::Bvh;
 pub use point::Point;
 pub use quaternion::Quaternion;
 pub use vector::Vector;
 
+pub use polynom::Polynom;
 pub use polynom2::Polynom2;
@@ -0,0 +1,167 @@
+//!
+//! Axis-aligned bounding box.
+//!
+//! An `Aabb` is the smallest box, aligned with the space axes, enclosing a set of points. They are
+//! cheap to intersect, and are used to quickly discard [Rays](crate::render::Ray) that cannot hit
+//! complex objects.
+//!
+
+use crate::render::Ray;
+
+use super::Point;
+
+///
+/// Axis-aligned bounding box.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Aabb {
+    ///
+    /// Create a new box from its two extreme corners.
+    ///
+    pub const fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    ///
+    /// Empty box.
+    ///
+    /// This box contains nothing, and growing it by any point yields a box containing only this
+    /// point.
+    ///
+    pub const EMPTY: Aabb = Aabb::new(
+        Point::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+    );
+
+    ///
+    /// Build the smallest box containing all the given points.
+    ///
+    pub fn from_points(points: impl IntoIterator<Item = Point>) -> Self {
+        points.into_iter().fold(Self::EMPTY, Self::grow)
+    }
+
+    ///
+    /// Return the smallest box containing both `self` and `p`.
+    ///
+    pub fn grow(self, p: Point) -> Self {
+        Self::new(
+            Point::new(
+                self.min.x.min(p.x),
+                self.min.y.min(p.y),
+                self.min.z.min(p.z),
+            ),
+            Point::new(
+                self.max.x.max(p.x),
+                self.max.y.max(p.y),
+                self.max.z.max(p.z),
+            ),
+        )
+    }
+
+    ///
+    /// Return the smallest box containing both `self` and `other`.
+    ///
+    pub fn union(self, other: Aabb) -> Self {
+        self.grow(other.min).grow(other.max)
+    }
+
+    ///
+    /// Return the box center.
+    ///
+    pub fn center(&self) -> Point {
+        self.min + (self.max - self.min) / 2.
+    }
+
+    ///
+    /// Compute the entry and exit distances of a [Ray] through the box.
+    ///
+    /// This uses the slab method: the ray is clipped against each pair of parallel planes, and
+    /// the box is hit if the resulting intervals overlap. The entry distance is negative when the
+    /// ray starts inside the box.
+    ///
+    pub fn intersect(&self, ray: Ray) -> Option<(f32, f32)> {
+        let slabs = [
+            (self.min.x, self.max.x, ray.orig.x, ray.dir.x),
+            (self.min.y, self.max.y, ray.orig.y, ray.dir.y),
+            (self.min.z, self.max.z, ray.orig.z, ray.dir.z),
+        ];
+
+        let (near, far) = slabs.into_iter().fold(
+            (f32::NEG_INFINITY, f32::INFINITY),
+            |(near, far), (min, max, orig, dir)| {
+                if dir == 0. {
+                    // The ray is parallel to the slab, and either always or never inside it.
+                    if (min..=max).contains(&orig) {
+                        (near, far)
+                    } else {
+                        (f32::INFINITY, f32::NEG_INFINITY)
+                    }
+                } else {
+                    let t0 = (min - orig) / dir;
+                    let t1 = (max - orig) / dir;
+
+                    (near.max(t0.min(t1)), far.min(t0.max(t1)))
+                }
+            },
+        );
+
+        if near <= far && far >= 0. {
+            Some((near, far))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maths::Vector;
+
+    #[test]
+    fn from_points_test() {
+        let b = Aabb::from_points([Point::new(1., -2., 3.), Point::new(-1., 2., 0.)]);
+
+        assert_eq!(b.min, Point::new(-1., -2., 0.));
+        assert_eq!(b.max, Point::new(1., 2., 3.));
+    }
+
+    #[test]
+    fn ray_hit_test() {
+        let b = Aabb::new(Point::new(-1., -1., 4.), Point::new(1., 1., 6.));
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+
+        assert_eq!(b.intersect(ray), Some((4., 6.)));
+    }
+
+    #[test]
+    fn ray_inside_test() {
+        let b = Aabb::new(Point::new(-1., -1., -1.), Point::new(1., 1., 1.));
+        let ray = Ray::new(Point::ORIGIN, Vector::X);
+
+        assert_eq!(b.intersect(ray), Some((-1., 1.)));
+    }
+
+    #[test]
+    fn ray_miss_test() {
+        let b = Aabb::new(Point::new(-1., -1., 4.), Point::new(1., 1., 6.));
+
+        assert_eq!(b.intersect(Ray::new(Point::ORIGIN, -Vector::Z)), None);
+        assert_eq!(b.intersect(Ray::new(Point::ORIGIN, Vector::X)), None);
+    }
+
+    #[test]
+    fn ray_on_face_test() {
+        let b = Aabb::new(Point::new(0., -1., 4.), Point::new(1., 1., 6.));
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+
+        assert_eq!(b.intersect(ray), Some((4., 6.)));
+    }
+}
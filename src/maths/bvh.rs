@@ -0,0 +1,221 @@
+//!
+//! Bounding volume hierarchy.
+//!
+//! A `Bvh` is a binary tree of [Aabbs](super::Aabb), each node bounding all of its children. It
+//! lets a [Ray](crate::render::Ray) be tested against a large number of primitives, by skipping
+//! whole sub-trees whose bounding box is missed. The tree only stores primitive indices, so that
+//! it can be used by any kind of primitive.
+//!
+
+use crate::render::{Intersection, Ray};
+
+use super::{Aabb, Point};
+
+/// Maximum number of primitives stored in a leaf.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf {
+        bounds: Aabb,
+        start: usize,
+        count: usize,
+    },
+    Inner {
+        bounds: Aabb,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> &Aabb {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Inner { bounds, .. } => bounds,
+        }
+    }
+}
+
+///
+/// Bounding volume hierarchy.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    /// Primitive indices, ordered so that each leaf refers to a contiguous range.
+    indices: Vec<usize>,
+}
+
+impl Bvh {
+    ///
+    /// Build a hierarchy over primitives, given their bounding boxes.
+    ///
+    /// Nodes are split at the median of their primitives centers, along their longest axis.
+    ///
+    pub fn new(boxes: &[Aabb]) -> Self {
+        let mut bvh = Self {
+            nodes: vec![],
+            indices: (0..boxes.len()).collect(),
+        };
+
+        if !boxes.is_empty() {
+            bvh.build(boxes, 0, boxes.len());
+        }
+        bvh
+    }
+
+    fn build(&mut self, boxes: &[Aabb], start: usize, end: usize) -> usize {
+        let range = &mut self.indices[start..end];
+        let bounds = range
+            .iter()
+            .fold(Aabb::EMPTY, |acc, i| acc.union(boxes[*i]));
+
+        let id = self.nodes.len();
+
+        if range.len() <= LEAF_SIZE {
+            self.nodes.push(Node::Leaf {
+                bounds,
+                start,
+                count: range.len(),
+            });
+            return id;
+        }
+
+        let size = bounds.max - bounds.min;
+        let axis = |p: Point| {
+            if size.x >= size.y && size.x >= size.z {
+                p.x
+            } else if size.y >= size.z {
+                p.y
+            } else {
+                p.z
+            }
+        };
+
+        let mid = range.len() / 2;
+        range.select_nth_unstable_by(mid, |a, b| {
+            axis(boxes[*a].center())
+                .partial_cmp(&axis(boxes[*b].center()))
+                .unwrap()
+        });
+
+        // Children are pushed after their parent, so the parent is patched once they're built.
+        self.nodes.push(Node::Leaf {
+            bounds,
+            start,
+            count: 0,
+        });
+        let left = self.build(boxes, start, start + mid);
+        let right = self.build(boxes, start + mid, end);
+        self.nodes[id] = Node::Inner {
+            bounds,
+            left,
+            right,
+        };
+
+        id
+    }
+
+    ///
+    /// Find the closest intersection between a [Ray] and the primitives.
+    ///
+    /// `hit` is called with the index of each primitive whose bounding box may hold an
+    /// intersection closer than the closest found so far, along with said closest distance.
+    ///
+    pub fn intersect(
+        &self,
+        ray: Ray,
+        mut hit: impl FnMut(usize, f32) -> Option<Intersection>,
+    ) -> Option<Intersection> {
+        let mut closest: Option<Intersection> = None;
+        let mut stack = vec![];
+
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+            let max = closest.map_or(f32::INFINITY, |c| c.dist);
+
+            match node.bounds().intersect(ray) {
+                Some((near, _)) if near <= max => {}
+                _ => continue,
+            }
+
+            match *node {
+                Node::Leaf { start, count, .. } => {
+                    for i in &self.indices[start..start + count] {
+                        let max = closest.map_or(f32::INFINITY, |c| c.dist);
+
+                        if let Some(inter) = hit(*i, max).filter(|inter| inter.dist < max) {
+                            closest = Some(inter);
+                        }
+                    }
+                }
+                Node::Inner { left, right, .. } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+
+        closest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maths::Vector;
+
+    fn unit_boxes() -> Vec<Aabb> {
+        (0..20)
+            .map(|i| {
+                let z = i as f32 * 2.;
+                Aabb::new(Point::new(-0.5, -0.5, z), Point::new(0.5, 0.5, z + 1.))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn closest_hit_test() {
+        let boxes = unit_boxes();
+        let bvh = Bvh::new(&boxes);
+        let ray = Ray::new(Point::new(0., 0., -5.), Vector::Z);
+
+        let inter = bvh.intersect(ray, |i, _| {
+            boxes[i]
+                .intersect(ray)
+                .map(|(near, _)| Intersection::new(near, ray.orig + ray.dir * near, -ray.dir))
+        });
+
+        assert_eq!(inter.map(|i| i.dist), Some(5.));
+    }
+
+    #[test]
+    fn miss_test() {
+        let boxes = unit_boxes();
+        let bvh = Bvh::new(&boxes);
+        let ray = Ray::new(Point::new(2., 0., -5.), Vector::Z);
+        let mut calls = 0;
+
+        let inter = bvh.intersect(ray, |_, _| {
+            calls += 1;
+            None
+        });
+
+        assert!(inter.is_none());
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn empty_test() {
+        let bvh = Bvh::new(&[]);
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+
+        assert!(bvh.intersect(ray, |_, _| unreachable!()).is_none());
+    }
+}
@@ -0,0 +1,185 @@
+//!
+//! Piecewise-constant distributions.
+//!
+//! Importance sampling draws random numbers following a distribution close to the function being
+//! integrated. When this function is only known as a table of values, e.g. the pixels of an
+//! image, it can be sampled through piecewise-constant 1D and 2D distributions.
+//!
+
+///
+/// Piecewise-constant distribution over `[0, 1)`, built from `n` non-negative values.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution1D {
+    func: Vec<f32>,
+    /// Cumulative distribution, with `n + 1` values from `0` to `1`.
+    cdf: Vec<f32>,
+    /// Integral of the function over `[0, 1)`.
+    integral: f32,
+}
+
+impl Distribution1D {
+    pub fn new(func: Vec<f32>) -> Self {
+        let n = func.len();
+        let mut cdf = Vec::with_capacity(n + 1);
+        cdf.push(0.);
+        for (i, f) in func.iter().enumerate() {
+            cdf.push(cdf[i] + f.max(0.) / n as f32);
+        }
+
+        let integral = cdf[n];
+        for (i, c) in cdf.iter_mut().enumerate() {
+            // A null function is sampled uniformly.
+            *c = if integral > 0. {
+                *c / integral
+            } else {
+                i as f32 / n as f32
+            };
+        }
+
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.func.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.func.is_empty()
+    }
+
+    pub fn integral(&self) -> f32 {
+        self.integral
+    }
+
+    ///
+    /// Draw a value in `[0, 1)` from a uniform random number `u`.
+    ///
+    /// Return the drawn value, its probability density, and the index of the piece it lies in.
+    ///
+    pub fn sample(&self, u: f32) -> (f32, f32, usize) {
+        let n = self.len();
+        let i = self.cdf.partition_point(|&c| c <= u).clamp(1, n) - 1;
+
+        let width = self.cdf[i + 1] - self.cdf[i];
+        let offset = if width > 0. {
+            (u - self.cdf[i]) / width
+        } else {
+            0.
+        };
+
+        let x = ((i as f32 + offset) / n as f32).min(1. - f32::EPSILON);
+
+        (x, self.pdf(i), i)
+    }
+
+    ///
+    /// Return the probability density of the values in the `i`-th piece.
+    ///
+    pub fn pdf(&self, i: usize) -> f32 {
+        if self.integral > 0. {
+            self.func[i].max(0.) / self.integral
+        } else {
+            1.
+        }
+    }
+}
+
+///
+/// Piecewise-constant distribution over `[0, 1)²`, built from a `width × height` table of
+/// non-negative values, stored row by row.
+///
+/// A row is first drawn from the marginal distribution of the rows, then a column from the
+/// distribution of the drawn row.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    pub fn new(width: usize, height: usize, func: &[f32]) -> Self {
+        let rows: Vec<Distribution1D> = func
+            .chunks(width)
+            .take(height)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(rows.iter().map(|r| r.integral()).collect());
+
+        Self { rows, marginal }
+    }
+
+    ///
+    /// Draw a point of `[0, 1)²` from a pair of uniform random numbers.
+    ///
+    /// Return the drawn point and its probability density.
+    ///
+    pub fn sample(&self, (u, v): (f32, f32)) -> ((f32, f32), f32) {
+        let (y, pdf_y, row) = self.marginal.sample(v);
+        let (x, pdf_x, _) = self.rows[row].sample(u);
+
+        ((x, y), pdf_x * pdf_y)
+    }
+
+    ///
+    /// Return the probability density of the point `(x, y)`.
+    ///
+    pub fn pdf(&self, (x, y): (f32, f32)) -> f32 {
+        let row = ((y * self.marginal.len() as f32) as usize).min(self.marginal.len() - 1);
+        let dist = &self.rows[row];
+        let col = ((x * dist.len() as f32) as usize).min(dist.len() - 1);
+
+        self.marginal.pdf(row) * dist.pdf(col)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distribution_1d_test() {
+        let dist = Distribution1D::new(vec![1., 3., 0., 4.]);
+
+        assert_eq!(dist.integral(), 2.);
+
+        let (x, pdf, i) = dist.sample(0.);
+        assert_eq!((x, pdf, i), (0., 0.5, 0));
+
+        // The third piece is null, so it can never be drawn.
+        let (x, pdf, i) = dist.sample(0.5);
+        assert_eq!(i, 3);
+        assert_eq!(pdf, 2.);
+        assert!((x - 0.75).abs() < 1e-6);
+
+        let (x, _, i) = dist.sample(0.25);
+        assert_eq!(i, 1);
+        assert!((x - 1. / 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn distribution_1d_null_test() {
+        let dist = Distribution1D::new(vec![0., 0.]);
+        let (x, pdf, _) = dist.sample(0.3);
+
+        assert!((x - 0.3).abs() < 1e-6);
+        assert_eq!(pdf, 1.);
+    }
+
+    #[test]
+    fn distribution_2d_test() {
+        let dist = Distribution2D::new(2, 2, &[1., 1., 0., 2.]);
+
+        let ((x, y), pdf) = dist.sample((0.9, 0.9));
+        assert!(x >= 0.5 && y >= 0.5);
+        assert!((pdf - 2.).abs() < 1e-6);
+        assert!((dist.pdf((x, y)) - pdf).abs() < 1e-6);
+
+        assert_eq!(dist.pdf((0.2, 0.7)), 0.);
+    }
+}
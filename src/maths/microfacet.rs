@@ -0,0 +1,124 @@
+//!
+//! Microfacet model.
+//!
+//! Rough surfaces are modeled as a multitude of tiny perfect mirrors, the microfacets, whose
+//! normals are spread around the surface normal. This module provides the building blocks of the
+//! Cook-Torrance specular BRDF:
+//! - The GGX (Trowbridge-Reitz) distribution of the microfacets normals.
+//! - The Smith masking-shadowing term, accounting for microfacets hidden by their neighbors.
+//! - The Schlick approximation of the Fresnel reflectance.
+//!
+//! Roughness is expressed by `alpha`, from `0` (perfect mirror) to `1` (very rough surface).
+//!
+
+use std::f32::consts::PI;
+
+use super::Vector;
+
+///
+/// GGX normal distribution, for a microfacet normal making an angle `θ` with the surface normal
+/// and `cos_h = cos(θ)`.
+///
+/// The distribution is normalized so that its integral over the hemisphere, weighted by `cos_h`,
+/// is 1.
+///
+pub fn ggx(cos_h: f32, alpha: f32) -> f32 {
+    if cos_h <= 0. {
+        return 0.;
+    }
+
+    let a2 = alpha * alpha;
+    let d = cos_h * cos_h * (a2 - 1.) + 1.;
+
+    a2 / (PI * d * d)
+}
+
+///
+/// Smith masking function for the GGX distribution, for a direction making an angle `θ` with the
+/// surface normal and `cos_v = cos(θ)`.
+///
+/// The masking-shadowing term of a pair of directions is the product of their masking functions.
+///
+pub fn smith_g1(cos_v: f32, alpha: f32) -> f32 {
+    if cos_v <= 0. {
+        return 0.;
+    }
+
+    let a2 = alpha * alpha;
+
+    2. * cos_v / (cos_v + (a2 + (1. - a2) * cos_v * cos_v).sqrt())
+}
+
+///
+/// Schlick approximation of the Fresnel reflectance, for a reflectance `f0` at normal incidence
+/// and `cos = cos(θ)` of the incidence angle.
+///
+pub fn schlick(f0: f32, cos: f32) -> f32 {
+    f0 + (1. - f0) * (1. - cos.clamp(0., 1.)).powi(5)
+}
+
+///
+/// Draw a microfacet normal around the unit vector `n`, following the GGX distribution.
+///
+/// The probability density of the returned normal `h` is `ggx(n · h) * (n · h)`.
+///
+pub fn sample_ggx(n: Vector, alpha: f32, (u, v): (f32, f32)) -> Vector {
+    let tan2 = alpha * alpha * u / (1. - u).max(1e-7);
+    let cos = (1. + tan2).sqrt().recip();
+    let sin = (1. - cos * cos).max(0.).sqrt();
+    let phi = 2. * PI * v;
+    let (t, b) = n.any_orthonormal_pair();
+
+    (t * (sin * phi.cos()) + b * (sin * phi.sin()) + n * cos).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ggx_normalized_test() {
+        for alpha in [0.2, 0.5, 1.] {
+            let steps = 20000;
+            let dtheta = PI / 2. / steps as f32;
+
+            let integral: f32 = (0..steps)
+                .map(|i| {
+                    let theta = (i as f32 + 0.5) * dtheta;
+                    let (sin, cos) = theta.sin_cos();
+
+                    ggx(cos, alpha) * cos * sin * dtheta * 2. * PI
+                })
+                .sum();
+
+            assert!((integral - 1.).abs() < 1e-2, "alpha {alpha}: {integral}");
+        }
+    }
+
+    #[test]
+    fn smith_g1_test() {
+        assert!((smith_g1(1., 0.5) - 1.).abs() < 1e-6);
+        assert!(smith_g1(0.1, 0.5) < smith_g1(0.5, 0.5));
+        assert_eq!(smith_g1(-0.5, 0.5), 0.);
+    }
+
+    #[test]
+    fn schlick_test() {
+        assert!((schlick(0.04, 1.) - 0.04).abs() < 1e-6);
+        assert!((schlick(0.04, 0.) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sample_ggx_above_test() {
+        let n = Vector::new(1., 2., -3.).normalize();
+
+        for u in [0., 0.3, 0.7, 0.99] {
+            for v in [0., 0.5, 0.9] {
+                let h = sample_ggx(n, 0.3, (u, v));
+
+                assert!(h.is_normalized());
+                assert!(h.dot(n) > 0.);
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//!
+//! Gradient noise.
+//!
+//! Perlin noise is a smooth pseudo-random function of space, whose features all have about the
+//! same size. It is the building block of most procedural textures: summing octaves of noise of
+//! decreasing size and amplitude gives fractal Brownian motion (fBm), whose details look natural
+//! at every scale.
+//!
+//! This module implements Ken Perlin's improved noise (2002), which is deterministic: the same
+//! point always gives the same value.
+//!
+
+use super::Point;
+
+/// Permutation of `0..256` from the reference implementation, hashing lattice coordinates.
+const PERMUTATION: [u8; 256] = [
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69,
+    142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148, 247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219,
+    203, 117, 35, 11, 32, 57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+    74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122, 60, 211, 133, 230,
+    220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25, 63, 161, 1, 216, 80, 73, 209, 76,
+    132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173,
+    186, 3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212, 207, 206,
+    59, 227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213, 119, 248, 152, 2, 44, 154, 163,
+    70, 221, 153, 101, 155, 167, 43, 172, 9, 129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232,
+    178, 185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162,
+    241, 81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157, 184, 84, 204,
+    176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93, 222, 114, 67, 29, 24, 72, 243, 141,
+    128, 195, 78, 66, 215, 61, 156, 180,
+];
+
+fn hash(i: i32) -> i32 {
+    PERMUTATION[(i & 255) as usize] as i32
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+///
+/// Dot product of the offset `(x, y, z)` with one of 12 gradient directions, picked by `hash`.
+///
+fn grad(hash: i32, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+///
+/// Perlin noise at `p`, roughly in `[-1, 1]`.
+///
+/// The noise is null on the integer lattice, and its features are about one unit wide.
+///
+pub fn perlin(p: Point) -> f32 {
+    let (fx, fy, fz) = (p.x.floor(), p.y.floor(), p.z.floor());
+    let (xi, yi, zi) = (fx as i32, fy as i32, fz as i32);
+    let (x, y, z) = (p.x - fx, p.y - fy, p.z - fz);
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+
+    let a = hash(xi) + yi;
+    let (aa, ab) = (hash(a) + zi, hash(a + 1) + zi);
+    let b = hash(xi + 1) + yi;
+    let (ba, bb) = (hash(b) + zi, hash(b + 1) + zi);
+
+    lerp(
+        w,
+        lerp(
+            v,
+            lerp(u, grad(hash(aa), x, y, z), grad(hash(ba), x - 1., y, z)),
+            lerp(
+                u,
+                grad(hash(ab), x, y - 1., z),
+                grad(hash(bb), x - 1., y - 1., z),
+            ),
+        ),
+        lerp(
+            v,
+            lerp(
+                u,
+                grad(hash(aa + 1), x, y, z - 1.),
+                grad(hash(ba + 1), x - 1., y, z - 1.),
+            ),
+            lerp(
+                u,
+                grad(hash(ab + 1), x, y - 1., z - 1.),
+                grad(hash(bb + 1), x - 1., y - 1., z - 1.),
+            ),
+        ),
+    )
+}
+
+///
+/// Sum `octaves` octaves of `noise`, each one twice as detailed and half as strong as the
+/// previous one.
+///
+fn octaves(p: Point, octaves: u32, noise: impl Fn(Point) -> f32) -> f32 {
+    let (mut sum, mut freq, mut amp) = (0., 1., 1.);
+
+    for _ in 0..octaves {
+        sum += amp * noise(Point::new(p.x * freq, p.y * freq, p.z * freq));
+        freq *= 2.;
+        amp *= 0.5;
+    }
+
+    sum
+}
+
+///
+/// Fractal Brownian motion: sum of `octaves` octaves of [perlin] noise.
+///
+/// The result is roughly in `[-1, 1]`.
+///
+pub fn fbm(p: Point, octaves: u32) -> f32 {
+    self::octaves(p, octaves, perlin)
+}
+
+///
+/// Turbulence: sum of `octaves` octaves of the absolute value of [perlin] noise.
+///
+/// The creases where the noise changes sign give a billowy look. The result is roughly in
+/// `[0, 1]`.
+///
+pub fn turbulence(p: Point, octaves: u32) -> f32 {
+    self::octaves(p, octaves, |p| perlin(p).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perlin_lattice_test() {
+        for (x, y, z) in [(0., 0., 0.), (1., 2., 3.), (-4., 7., -1.)] {
+            assert_eq!(perlin(Point::new(x, y, z)), 0.);
+        }
+    }
+
+    #[test]
+    fn perlin_range_test() {
+        for i in 0..1000 {
+            let t = i as f32 * 0.137;
+            let n = perlin(Point::new(t, t * 0.7 + 1.3, -t * 0.3));
+
+            assert!((-1.1..=1.1).contains(&n));
+        }
+    }
+
+    #[test]
+    fn perlin_continuous_test() {
+        let p = Point::new(1.3, 2.7, -0.4);
+        let q = Point::new(1.3001, 2.7, -0.4);
+
+        assert!((perlin(p) - perlin(q)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn fbm_single_octave_test() {
+        let p = Point::new(0.3, 1.7, 2.2);
+
+        assert_eq!(fbm(p, 1), perlin(p));
+        assert_eq!(turbulence(p, 1), perlin(p).abs());
+    }
+}
@@ -0,0 +1,230 @@
+//!
+//! Polynomials of arbitrary degree.
+//!
+//! Unlike [Polynom2](super::Polynom2), which has a closed form solution, roots of higher degree
+//! polynomials are found numerically. The roots of the derivative split the search interval into
+//! monotonic pieces, each of them holding at most one root, which is then found by bisection.
+//!
+
+use std::ops::{Add, Mul};
+
+///
+/// Polynomial of arbitrary degree.
+///
+/// Coefficients are stored in ascending power order, that is `coefs[i]` is the factor of `x^i`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynom {
+    pub coefs: Vec<f32>,
+}
+
+impl Polynom {
+    ///
+    /// Create a new polynomial, with coefficients in ascending power order.
+    ///
+    pub fn new(coefs: Vec<f32>) -> Self {
+        Self { coefs }
+    }
+
+    ///
+    /// Zero polynomial.
+    ///
+    pub fn zero() -> Self {
+        Self::new(vec![])
+    }
+
+    ///
+    /// Return the polynomial degree.
+    ///
+    /// The zero polynomial is given a degree of 0.
+    ///
+    pub fn degree(&self) -> usize {
+        self.coefs.len().saturating_sub(1)
+    }
+
+    ///
+    /// Evaluate the polynomial at `x`, using Horner's method.
+    ///
+    pub fn eval(&self, x: f32) -> f32 {
+        self.coefs.iter().rev().fold(0., |acc, c| acc * x + c)
+    }
+
+    ///
+    /// Compute the derivative polynomial.
+    ///
+    pub fn derivative(&self) -> Self {
+        Self::new(
+            self.coefs
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| i as f32 * c)
+                .collect(),
+        )
+    }
+
+    ///
+    /// Find all the roots in `[lo, hi]`, in ascending order.
+    ///
+    /// Roots of even multiplicity (where the polynomial touches 0 without crossing it) may be
+    /// missed.
+    ///
+    pub fn roots_in(&self, lo: f32, hi: f32) -> Vec<f32> {
+        if self.degree() == 0 {
+            return vec![];
+        }
+
+        let mut bounds = vec![lo];
+        bounds.extend(self.derivative().roots_in(lo, hi));
+        bounds.push(hi);
+
+        bounds
+            .windows(2)
+            .filter_map(|w| self.bisect(w[0], w[1]))
+            .fold(vec![], |mut roots, r| {
+                // Adjacent pieces share their bounds, and may both report a root lying on it.
+                if roots.last().is_none_or(|l| r - l > 1e-6) {
+                    roots.push(r);
+                }
+                roots
+            })
+    }
+
+    ///
+    /// Find the smallest root in `[lo, hi]`.
+    ///
+    pub fn first_root_in(&self, lo: f32, hi: f32) -> Option<f32> {
+        self.roots_in(lo, hi).first().copied()
+    }
+
+    ///
+    /// Find a root in `[lo, hi]`, assuming the polynomial is monotonic on this interval.
+    ///
+    fn bisect(&self, mut lo: f32, mut hi: f32) -> Option<f32> {
+        let mut f_lo = self.eval(lo);
+        let f_hi = self.eval(hi);
+
+        if f_lo == 0. {
+            return Some(lo);
+        } else if f_hi == 0. {
+            return Some(hi);
+        } else if f_lo.signum() == f_hi.signum() {
+            return None;
+        }
+
+        for _ in 0..64 {
+            let mid = (lo + hi) / 2.;
+            let f_mid = self.eval(mid);
+
+            if f_mid == 0. || hi - lo <= 1e-6 * mid.abs().max(1.) {
+                return Some(mid);
+            } else if f_mid.signum() == f_lo.signum() {
+                lo = mid;
+                f_lo = f_mid;
+            } else {
+                hi = mid;
+            }
+        }
+
+        Some((lo + hi) / 2.)
+    }
+}
+
+impl Add for Polynom {
+    type Output = Polynom;
+
+    ///
+    /// Add two polynomials, term by term.
+    ///
+    fn add(self, rhs: Self) -> Self::Output {
+        let (mut long, short) = if self.coefs.len() >= rhs.coefs.len() {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+
+        long.coefs
+            .iter_mut()
+            .zip(short.coefs)
+            .for_each(|(l, s)| *l += s);
+        long
+    }
+}
+
+impl Mul for Polynom {
+    type Output = Polynom;
+
+    ///
+    /// Multiply two polynomials.
+    ///
+    fn mul(self, rhs: Self) -> Self::Output {
+        if self.coefs.is_empty() || rhs.coefs.is_empty() {
+            return Self::zero();
+        }
+
+        let mut coefs = vec![0.; self.coefs.len() + rhs.coefs.len() - 1];
+
+        for (i, a) in self.coefs.iter().enumerate() {
+            for (j, b) in rhs.coefs.iter().enumerate() {
+                coefs[i + j] += a * b;
+            }
+        }
+
+        Self::new(coefs)
+    }
+}
+
+impl Mul<f32> for Polynom {
+    type Output = Polynom;
+
+    ///
+    /// Scale each coefficient by a given factor.
+    ///
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self::new(self.coefs.into_iter().map(|c| c * rhs).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eval_test() {
+        let p = Polynom::new(vec![1., -2., 3.]);
+
+        assert_eq!(p.eval(2.), 9.);
+    }
+
+    #[test]
+    fn derivative_test() {
+        let p = Polynom::new(vec![1., -2., 3., 4.]);
+
+        assert_eq!(p.derivative(), Polynom::new(vec![-2., 6., 12.]));
+    }
+
+    #[test]
+    fn add_mul_test() {
+        let p = Polynom::new(vec![-1., 1.]);
+        let q = Polynom::new(vec![1., 1.]);
+
+        assert_eq!(p.clone() * q.clone(), Polynom::new(vec![-1., 0., 1.]));
+        assert_eq!(p + q * 2., Polynom::new(vec![1., 3.]));
+    }
+
+    #[test]
+    fn quartic_roots_test() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        let p = Polynom::new(vec![24., -50., 35., -10., 1.]);
+        let roots = p.roots_in(0., 10.);
+
+        assert_eq!(roots.len(), 4);
+        roots
+            .iter()
+            .zip([1., 2., 3., 4.])
+            .for_each(|(r, e)| assert!((r - e).abs() <= 1e-4));
+
+        assert!((p.first_root_in(2.5, 10.).unwrap() - 3.).abs() <= 1e-4);
+        assert_eq!(p.first_root_in(4.5, 10.), None);
+    }
+}
@@ -38,7 +38,7 @@ impl Polynom2<f32> {
             None
         } else if delta <= 1e-7 {
             // This should be changed to f32::next_up(0.) once stabilized.
-            let r = -self.b / 2. * self.a;
+            let r = -self.b / (2. * self.a);
             Some([r, f32::NAN])
         } else {
             let sqr = delta.sqrt();
@@ -54,3 +54,31 @@ impl Polynom2<f32> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_roots_test() {
+        let p = Polynom2::new(1., 0., 1.);
+
+        assert_eq!(p.roots(), None);
+    }
+
+    #[test]
+    fn tangent_root_test() {
+        let p = Polynom2::new(2., -8., 8.);
+        let [r, nan] = p.roots().unwrap();
+
+        assert_eq!(r, 2.);
+        assert!(nan.is_nan());
+    }
+
+    #[test]
+    fn sorted_roots_test() {
+        let p = Polynom2::new(-1., 1., 6.);
+
+        assert_eq!(p.roots(), Some([-2., 3.]));
+    }
+}
@@ -150,6 +150,34 @@ impl Quaternion {
         }
     }
 
+    ///
+    /// Create a quaternion for the rotation mapping the `X`, `Y` and `Z` axes to `x`, `y` and
+    /// `z`.
+    ///
+    /// The inputs must form a right-handed orthonormal basis, i.e. `x × y = z`.
+    ///
+    /// This uses Shepperd's method, which extracts the largest component of the quaternion first
+    /// to stay numerically stable.
+    ///
+    pub fn from_basis(x: Vector, y: Vector, z: Vector) -> Self {
+        let trace = x.x + y.y + z.z;
+
+        if trace > 0. {
+            let s = 2. * (1. + trace).sqrt();
+            Self::new(s / 4., (y.z - z.y) / s, (z.x - x.z) / s, (x.y - y.x) / s)
+        } else if x.x > y.y && x.x > z.z {
+            let s = 2. * (1. + x.x - y.y - z.z).sqrt();
+            Self::new((y.z - z.y) / s, s / 4., (y.x + x.y) / s, (z.x + x.z) / s)
+        } else if y.y > z.z {
+            let s = 2. * (1. + y.y - x.x - z.z).sqrt();
+            Self::new((z.x - x.z) / s, (y.x + x.y) / s, s / 4., (z.y + y.z) / s)
+        } else {
+            let s = 2. * (1. + z.z - x.x - y.y).sqrt();
+            Self::new((x.y - y.x) / s, (z.x + x.z) / s, (z.y + y.z) / s, s / 4.)
+        }
+        .normalize()
+    }
+
     ///
     /// Return the conjugate of the quaternion.
     ///
@@ -408,6 +436,29 @@ mod tests {
         assert!(adj_z <= threshold);
     }
 
+    #[test]
+    fn from_basis_test() {
+        let bases = [
+            (Vector::X, Vector::Y, Vector::Z),
+            (-Vector::X, Vector::Y, -Vector::Z),
+            (Vector::Z, -Vector::Y, Vector::X),
+            (-Vector::Z, -Vector::X, Vector::Y),
+            (
+                Vector::new(1., 1., 0.).normalize(),
+                Vector::new(-1., 1., 1.).normalize(),
+                Vector::new(1., -1., 2.).normalize(),
+            ),
+        ];
+
+        for (x, y, z) in bases {
+            let q = Quaternion::from_basis(x, y, z);
+
+            assert!((q * Vector::X - x).magn() <= 1e-5);
+            assert!((q * Vector::Y - y).magn() <= 1e-5);
+            assert!((q * Vector::Z - z).magn() <= 1e-5);
+        }
+    }
+
     #[test]
     fn simple_rotation_test() {
         let q_rot_x = Quaternion::from_x_rot(f32::to_radians(45.));
@@ -0,0 +1,121 @@
+//!
+//! Sampling functions.
+//!
+//! Monte Carlo rendering needs to draw random directions or points following given
+//! distributions. The functions of this module warp uniform random numbers in `[0, 1)` into
+//! such distributions.
+//!
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use super::Vector;
+
+///
+/// Map a uniform sample of the unit square to a uniform sample of the unit disk.
+///
+/// This uses Shirley's concentric mapping, which keeps neighboring samples close to each other,
+/// and thus preserves stratification.
+///
+pub fn concentric_disk((u, v): (f32, f32)) -> (f32, f32) {
+    let (x, y) = (2. * u - 1., 2. * v - 1.);
+
+    if x == 0. && y == 0. {
+        return (0., 0.);
+    }
+
+    let (r, theta) = if x.abs() > y.abs() {
+        (x, FRAC_PI_4 * (y / x))
+    } else {
+        (y, FRAC_PI_2 - FRAC_PI_4 * (x / y))
+    };
+
+    (r * theta.cos(), r * theta.sin())
+}
+
+///
+/// Draw a direction in the hemisphere around the unit vector `n`, with a probability
+/// proportional to the cosine of its angle with `n`.
+///
+/// The probability density of the returned direction `w` is `n · w / π`.
+///
+pub fn cosine_hemisphere(n: Vector, u: (f32, f32)) -> Vector {
+    let (x, y) = concentric_disk(u);
+    let z = (1. - x * x - y * y).max(0.).sqrt();
+    let (t, b) = n.any_orthonormal_pair();
+
+    (t * x + b * y + n * z).normalize()
+}
+
+///
+/// Draw a direction uniformly on the unit sphere.
+///
+pub fn uniform_sphere((u, v): (f32, f32)) -> Vector {
+    let z = 1. - 2. * u;
+    let r = (1. - z * z).max(0.).sqrt();
+    let phi = 2. * PI * v;
+
+    Vector::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+///
+/// Power heuristic of multiple importance sampling, weighting a sample drawn with density `pdf`
+/// against another technique of density `other`.
+///
+/// Each density should be multiplied by the number of samples drawn with its technique.
+///
+pub fn power_heuristic(pdf: f32, other: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other * other);
+
+    if a + b > 0. {
+        a / (a + b)
+    } else {
+        0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: [f32; 5] = [0., 0.2, 0.5, 0.7, 0.99];
+
+    #[test]
+    fn concentric_disk_in_disk_test() {
+        for u in GRID {
+            for v in GRID {
+                let (x, y) = concentric_disk((u, v));
+                assert!(x * x + y * y <= 1. + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn cosine_hemisphere_above_test() {
+        let n = Vector::new(1., 2., -3.).normalize();
+
+        for u in GRID {
+            for v in GRID {
+                let w = cosine_hemisphere(n, (u, v));
+
+                assert!(w.is_normalized());
+                assert!(w.dot(n) >= -1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn power_heuristic_test() {
+        assert_eq!(power_heuristic(1., 0.), 1.);
+        assert_eq!(power_heuristic(1., 1.), 0.5);
+        assert!((power_heuristic(1., 2.) + power_heuristic(2., 1.) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn uniform_sphere_normalized_test() {
+        for u in GRID {
+            for v in GRID {
+                assert!(uniform_sphere((u, v)).is_normalized());
+            }
+        }
+    }
+}
@@ -21,7 +21,7 @@
 //! **Multiplication**:
 //! There are two kind of vector multiplication:
 //! - The dot (or scalar) product returns a scalar equals to `||a|| * ||b|| * cos(θ)`, with `θ`
-//! being the angle between `a` and `b`.
+//!   being the angle between `a` and `b`.
 //! - The cross product is a third vector, orthogonal to both.
 //!
 //! **Scalar Multiplication and Division**:
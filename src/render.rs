@@ -8,77 +8,409 @@
 //! reflection or transparency, in a recursive fashion.
 //!
 
+pub mod adaptive;
+pub mod background;
 pub mod camera;
+pub mod film;
+pub mod filter;
+pub mod integrator;
 pub mod intersection;
+pub mod progressive;
 pub mod rays;
+pub mod region;
+pub mod sampler;
 
+pub use adaptive::Adaptive;
+pub use background::Background;
 pub use camera::*;
+pub use film::Film;
+pub use filter::Filter;
+pub use integrator::{Integrator, PathTracer};
 pub use intersection::Intersection;
+pub use progressive::Progressive;
 pub use rays::Ray;
+pub use region::Region;
+pub use sampler::Sampler;
 
-use crate::{colors::Rgba, scene::Scene};
+use nannou::{
+    image::{self, RgbaImage},
+    rand::{rngs::SmallRng, Rng, SeedableRng},
+};
+
+use crate::{
+    colors::{Color, Rgba},
+    scene::Scene,
+};
 
 ///
 /// Renderer.
 ///
 pub struct Renderer {
-    background: Rgba,
-    shaders: Vec<Box<dyn Shader>>,
+    background: Background,
+    integrator: Box<dyn Integrator>,
+    sampler: Sampler,
+    adaptive: Option<Adaptive>,
+    filter: Filter,
+    region: Option<Region>,
+    encoder: shader::GammaEncoder,
 }
 
 impl Renderer {
-    pub fn new(background: Rgba) -> Self {
-        let fast = Box::new(shader::Fast) as Box<dyn Shader>;
-        let gamma_enc = Box::<shader::GammaEncoder>::default() as Box<dyn Shader>;
-
+    pub fn new(background: impl Into<Background>) -> Self {
         Self {
-            background,
-            shaders: vec![fast, gamma_enc],
+            background: background.into(),
+            integrator: Box::new(integrator::Shading::default()),
+            sampler: Default::default(),
+            adaptive: None,
+            filter: Default::default(),
+            region: None,
+            encoder: Default::default(),
         }
     }
 
+    ///
+    /// Set the [Sampler] used to place rays inside each pixel.
+    ///
+    pub fn with_sampler(mut self, sampler: Sampler) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    ///
+    /// Enable [Adaptive] anti-aliasing when rendering whole frames.
+    ///
+    /// When enabled, the [Sampler] is ignored by [render_frame](Self::render_frame).
+    ///
+    pub fn with_adaptive(mut self, adaptive: Adaptive) -> Self {
+        self.adaptive = Some(adaptive);
+        self
+    }
+
+    ///
+    /// Set the reconstruction [Filter] used when rendering whole frames.
+    ///
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    ///
+    /// Only render a [Region] of the camera [Sensor] when rendering whole frames.
+    ///
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    ///
+    /// Set the [Integrator] computing the color of each sample.
+    ///
+    /// Defaults to a [Shading](integrator::Shading) integrator with a [Fast](shader::Fast)
+    /// shader.
+    ///
+    pub fn with_integrator(mut self, integrator: impl Integrator + 'static) -> Self {
+        self.integrator = Box::new(integrator);
+        self
+    }
+
+    ///
+    /// Render a single pixel.
+    ///
+    /// Each sample given by the `Renderer`'s [Sampler] is rendered, then their colors are
+    /// averaged in linear space, before being gamma encoded. As neighboring pixels are not
+    /// rendered, the reconstruction [Filter] is not applied.
+    ///
+    /// Pixels outside of the camera [Sensor] get the background seen along the camera direction.
+    ///
     pub fn render_pixel(&self, x: u32, y: u32, camera: &Camera, scene: &Scene) -> Rgba {
-        let Some(ray) = camera.get_ray(x, y) else {
-            eprintln!("WARN: trying to render outside of the camera sensor at ({}, {})", x, y);
-            return self.background
+        if !camera.sensor().has_pixel(x, y) {
+            eprintln!(
+                "WARN: trying to render outside of the camera sensor at ({}, {})",
+                x, y
+            );
+            return self
+                .encoder
+                .encode(self.background.color(camera.dir()))
+                .to_rgba();
+        }
+
+        self.encoder
+            .encode(self.sample_pixel(x, y, camera, scene))
+            .to_rgba()
+    }
+
+    ///
+    /// Render the whole camera [Sensor].
+    ///
+    /// Samples are accumulated in a [Film], which reconstructs the pixels using the
+    /// `Renderer`'s [Filter].
+    ///
+    pub fn render_frame(&self, camera: &Camera, scene: &Scene) -> RgbaImage {
+        self.develop(&self.render_film(camera, scene))
+    }
+
+    ///
+    /// Convert a linear [Film] into a gamma encoded image.
+    ///
+    /// With a [Region], the image is either cropped to the region, or the pixels outside of it are
+    /// transparent.
+    ///
+    pub fn develop(&self, film: &Film) -> RgbaImage {
+        let region = self.region(film.width(), film.height());
+        let develop = |x: u32, y: u32| {
+            image::Rgba(self.encoder.encode(film.pixel(x, y)).to_rgba().to_array())
         };
 
-        let inters = scene
-            .objects
+        if region.is_cropped() {
+            RgbaImage::from_fn(region.width(), region.height(), |x, y| {
+                develop(x + region.x(), y + region.y())
+            })
+        } else {
+            RgbaImage::from_fn(film.width(), film.height(), |x, y| {
+                if region.contains(x, y) {
+                    develop(x, y)
+                } else {
+                    image::Rgba([0, 0, 0, 0])
+                }
+            })
+        }
+    }
+
+    ///
+    /// Start a [Progressive] render of the whole camera [Sensor].
+    ///
+    pub fn start_progressive(&self, camera: &Camera) -> Progressive {
+        let (width, height) = (camera.sensor().width(), camera.sensor().height());
+
+        Progressive::new(width, height, self.filter)
+    }
+
+    ///
+    /// Run a single pass of a [Progressive] render, adding one sample per pixel of the `Renderer`'s
+    /// [Region].
+    ///
+    /// The first pass samples the pixel centers, to quickly give a clean preview, and later
+    /// passes place their sample randomly in each pixel. The `Renderer`'s [Sampler] and
+    /// [Adaptive] settings are ignored.
+    ///
+    pub fn render_pass(&self, progressive: &mut Progressive, camera: &Camera, scene: &Scene) {
+        let region = self.sampled_region(progressive.film().width(), progressive.film().height());
+        let pass = progressive.passes() as u64;
+
+        for (x, y) in region.pixels() {
+            let mut rng = pixel_rng(x, y, pass);
+            let (dx, dy) = if pass == 0 { (0.5, 0.5) } else { rng.gen() };
+
+            let (sx, sy) = (x as f32 + dx, y as f32 + dy);
+            let color = self.render_sample(sx, sy, camera, scene, &mut rng);
+            progressive.add_sample(sx, sy, color);
+        }
+
+        progressive.end_pass();
+    }
+
+    ///
+    /// Render the whole camera [Sensor] into a [Film], in linear space.
+    ///
+    /// Only the pixels of the `Renderer`'s [Region], and the ones around it reached by the
+    /// [Filter], get samples, the film keeping the size of the sensor.
+    ///
+    pub fn render_film(&self, camera: &Camera, scene: &Scene) -> Film {
+        let (width, height) = (camera.sensor().width(), camera.sensor().height());
+        let region = self.sampled_region(width, height);
+        let mut film = Film::new(width, height, self.filter);
+
+        match self.adaptive {
+            // Adaptive anti-aliasing already averages each pixel samples, so the pixel color
+            // is splatted as a single sample at its center.
+            Some(adaptive) => self
+                .render_adaptive(adaptive, region, camera, scene)
+                .into_iter()
+                .zip(region.pixels())
+                .for_each(|(color, (x, y))| {
+                    film.add_sample(x as f32 + 0.5, y as f32 + 0.5, color);
+                }),
+            None => {
+                for (x, y) in region.pixels() {
+                    let mut rng = pixel_rng(x, y, 0);
+
+                    for (dx, dy) in self.sampler.samples_with(&mut rng) {
+                        let (sx, sy) = (x as f32 + dx, y as f32 + dy);
+                        let color = self.render_sample(sx, sy, camera, scene, &mut rng);
+                        film.add_sample(sx, sy, color);
+                    }
+                }
+            }
+        }
+
+        film
+    }
+
+    ///
+    /// Return the [Region] to render in a frame of `width` x `height` pixels, which is the whole
+    /// frame if the `Renderer` has no region.
+    ///
+    fn region(&self, width: u32, height: u32) -> Region {
+        self.region
+            .map_or(Region::full(width, height), |r| r.clamp(width, height))
+    }
+
+    ///
+    /// Return the pixels to sample in a frame of `width` x `height` pixels: the [Region], padded
+    /// so that its pixels get the samples of all their neighbors reached by the [Filter].
+    ///
+    /// One more pixel is added, so that [Adaptive] anti-aliasing compares the padding pixels with
+    /// all their neighbors, as in the full frame.
+    ///
+    fn sampled_region(&self, width: u32, height: u32) -> Region {
+        let margin = self.filter.radius().ceil() as u32 + 1;
+
+        self.region(width, height)
+            .padded(margin)
+            .clamp(width, height)
+    }
+
+    ///
+    /// Compute the linear color of a pixel, by averaging the [Sampler]'s samples.
+    ///
+    fn sample_pixel(&self, x: u32, y: u32, camera: &Camera, scene: &Scene) -> Color {
+        let mut rng = pixel_rng(x, y, 0);
+        let samples = self.sampler.samples_with(&mut rng);
+        let sum = samples
             .iter()
-            .filter_map(|o| o.intersect(ray).zip(Some(o)));
+            .map(|(dx, dy)| {
+                self.render_sample(x as f32 + dx, y as f32 + dy, camera, scene, &mut rng)
+            })
+            .fold(Color::TRANSPARENT, |acc, c| acc + c);
 
-        let closest = inters.min_by(|(i, _), (i2, _)| i.dist.partial_cmp(&i2.dist).unwrap());
+        sum / samples.len() as f32
+    }
 
-        closest
-            .map(|(inter, obj)| {
-                self.shaders.iter().fold(Rgba::WHITE, |col, s| {
-                    s.compute_color(ray, obj.as_ref(), inter, col, scene)
-                })
+    ///
+    /// Render a [Region] of a frame with [Adaptive] anti-aliasing.
+    ///
+    /// A first pass renders a single sample at the center of each pixel. Pixels differing from
+    /// any of their direct neighbors, or whose samples are too noisy, are then refined. Pixels
+    /// are returned row by row.
+    ///
+    fn render_adaptive(
+        &self,
+        adaptive: Adaptive,
+        region: Region,
+        camera: &Camera,
+        scene: &Scene,
+    ) -> Vec<Color> {
+        let first: Vec<Color> = region
+            .pixels()
+            .map(|(x, y)| {
+                let mut rng = pixel_rng(x, y, 0);
+                self.render_sample(x as f32 + 0.5, y as f32 + 0.5, camera, scene, &mut rng)
             })
-            .unwrap_or(self.background)
+            .collect();
+        let at =
+            |x: u32, y: u32| first[((y - region.y()) * region.width() + x - region.x()) as usize];
+
+        region
+            .pixels()
+            .map(|(x, y)| {
+                let mut rng = pixel_rng(x, y, 1);
+                let center = at(x, y);
+                let neighbors = [
+                    (x.wrapping_sub(1), y),
+                    (x + 1, y),
+                    (x, y.wrapping_sub(1)),
+                    (x, y + 1),
+                ];
+
+                let edge = neighbors
+                    .into_iter()
+                    .filter(|(nx, ny)| region.contains(*nx, *ny))
+                    .any(|(nx, ny)| adaptive.differ(center, at(nx, ny)));
+
+                // A second sample at the pixel center estimates its variance.
+                let (center, noisy) = match adaptive.variance() {
+                    Some(_) if !edge => {
+                        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                        let probe = self.render_sample(px, py, camera, scene, &mut rng);
+
+                        ((center + probe) / 2., adaptive.noisy(&[center, probe]))
+                    }
+                    _ => (center, false),
+                };
+
+                if edge || noisy {
+                    adaptive.refine((x as f32, y as f32), 1., center, 0, &mut |sx, sy| {
+                        self.render_sample(sx, sy, camera, scene, &mut rng)
+                    })
+                } else {
+                    center
+                }
+            })
+            .collect()
+    }
+
+    ///
+    /// Render a single sample, at the sub-pixel position `(x, y)`.
+    ///
+    /// The returned color is in linear space. Cameras with a [Lens] shoot the ray
+    /// from a random point of the lens. The lens and the [Integrator] draw their random numbers
+    /// from `rng`.
+    ///
+    pub fn render_sample(
+        &self,
+        x: f32,
+        y: f32,
+        camera: &Camera,
+        scene: &Scene,
+        rng: &mut SmallRng,
+    ) -> Color {
+        let ray = match camera.lens() {
+            Some(_) => camera.get_lens_ray(x, y, rng.gen()),
+            None => camera.get_subpixel_ray(x, y),
+        };
+        let Some(ray) = ray else {
+            return Color::TRANSPARENT
+        };
+
+        self.integrator.radiance(ray, scene, &self.background, rng)
     }
 }
 
+///
+/// Create the random number generator of the pixel `(x, y)`, for the given `pass`.
+///
+/// Seeding from the pixel makes renders reproducible, whatever the order pixels are rendered in.
+///
+fn pixel_rng(x: u32, y: u32, pass: u64) -> SmallRng {
+    SmallRng::seed_from_u64((pass << 40) ^ ((y as u64) << 20) ^ x as u64)
+}
+
 impl Default for Renderer {
     fn default() -> Self {
-        Self::new(Rgba::BLACK)
+        Self::new(Background::default())
     }
 }
 
 pub mod shader {
     use super::*;
-    use crate::scene::Object;
+    use crate::scene::{Object, Surface};
 
-    pub trait Shader {
+    pub trait Shader: Send + Sync {
+        ///
+        /// Refine the `color` computed by the previous shaders for the `intersection` of `ray`
+        /// with `object`.
+        ///
+        /// Random decisions, such as sampling area lights, draw from the per-pixel `rng`.
+        ///
         fn compute_color(
             &self,
             ray: Ray,
             object: &dyn Object,
             intersection: Intersection,
-            color: Rgba,
+            color: Color,
             scene: &Scene,
-        ) -> Rgba;
+            rng: &mut SmallRng,
+        ) -> Color;
     }
 
     pub struct Fast;
@@ -89,20 +421,65 @@ pub mod shader {
             ray: Ray,
             _object: &dyn Object,
             intersection: Intersection,
-            color: Rgba,
+            color: Color,
             _scene: &Scene,
-        ) -> Rgba {
+            _rng: &mut SmallRng,
+        ) -> Color {
             let intensity = ray.dir.dot(-intersection.normal);
-            let [r, g, b, _] = color.to_array();
 
-            let r = ((r as f32) * intensity) as u8;
-            let g = ((g as f32) * intensity) as u8;
-            let b = ((b as f32) * intensity) as u8;
+            Color::from_rgb(
+                color.r * intensity,
+                color.g * intensity,
+                color.b * intensity,
+            )
+        }
+    }
+
+    ///
+    /// Direct lighting shader.
+    ///
+    /// Evaluates the object [Material](crate::scene::Material) BRDF for the light coming straight
+    /// from each [Light](crate::scene::Light) which isn't shadowed, plus the scene ambient light
+    /// and the material emission. The color computed by the previous shaders is used as base color.
+    ///
+    pub struct Direct;
+
+    /// Offset applied to shadow rays origins, to avoid hitting the surface they leave.
+    const EPSILON: f32 = 1e-4;
+
+    impl Shader for Direct {
+        fn compute_color(
+            &self,
+            ray: Ray,
+            object: &dyn Object,
+            intersection: Intersection,
+            color: Color,
+            scene: &Scene,
+            rng: &mut SmallRng,
+        ) -> Color {
+            let surface = Surface {
+                color,
+                ..object.material().surface(&intersection)
+            };
+            let intersection = intersection.face_forward(ray.dir);
+            let normal = intersection.normal;
+            let orig = intersection.pos + intersection.geometric_normal * EPSILON;
+            let wo = -ray.dir;
+
+            let radiance = surface.emission
+                + color * scene.ambiant
+                + scene.direct_light(orig, normal, wo, &surface, false, rng);
 
-            Rgba::from_rgb(r, g, b)
+            Color { a: 1., ..radiance }
         }
     }
 
+    ///
+    /// Gamma encoding of linear colors.
+    ///
+    /// Displays expect colors to be gamma encoded, so the `Renderer` encodes each pixel once
+    /// all of its samples have been averaged.
+    ///
     pub struct GammaEncoder {
         factor: f32,
     }
@@ -112,22 +489,19 @@ pub mod shader {
             Self { factor }
         }
 
-        pub fn encode(&self, color: Rgba) -> Rgba {
-            let [r, g, b, a] = color.to_array();
+        pub fn encode(&self, color: Color) -> Color {
+            let enc = |c: f32| c.max(0.).powf(self.factor.recip());
 
-            let mut f_r = (r as f32) / 255.;
-            let mut f_g = (g as f32) / 255.;
-            let mut f_b = (b as f32) / 255.;
-
-            f_r = f_r.powf(self.factor.recip());
-            f_g = f_g.powf(self.factor.recip());
-            f_b = f_b.powf(self.factor.recip());
+            Color::new(enc(color.r), enc(color.g), enc(color.b), color.a)
+        }
 
-            let r = (f_r * 255.) as u8;
-            let g = (f_g * 255.) as u8;
-            let b = (f_b * 255.) as u8;
+        ///
+        /// Inverse of [encode](Self::encode), bringing an encoded color back to linear space.
+        ///
+        pub fn decode(&self, color: Color) -> Color {
+            let dec = |c: f32| c.max(0.).powf(self.factor);
 
-            Rgba::new(r, g, b, a)
+            Color::new(dec(color.r), dec(color.g), dec(color.b), color.a)
         }
     }
 
@@ -137,9 +511,10 @@ pub mod shader {
             _ray: Ray,
             _object: &dyn Object,
             _intersection: Intersection,
-            color: Rgba,
+            color: Color,
             _scene: &Scene,
-        ) -> Rgba {
+            _rng: &mut SmallRng,
+        ) -> Color {
             self.encode(color)
         }
     }
@@ -152,3 +527,135 @@ pub mod shader {
 }
 
 pub use shader::Shader;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        maths::{Point, Vector},
+        scene::{lights::Rect, objects::Sphere, Light, Material, Object},
+    };
+
+    fn scene() -> Scene {
+        let sphere = Sphere::new(Point::new(0., 0., 4.), 1.).with_material(Material::DEFAULT);
+
+        Scene::new(vec![Box::new(sphere) as Box<dyn Object>], vec![], 0.)
+    }
+
+    fn camera() -> Camera {
+        Camera::default().with_sensor(Sensor::new(8, 8))
+    }
+
+    #[test]
+    fn reproducible_test() {
+        let renderer = Renderer::new(Background::gradient(Color::BLACK, Color::WHITE))
+            .with_integrator(PathTracer::default())
+            .with_sampler(Sampler::jittered(2));
+        let (camera, scene) = (camera(), scene());
+
+        assert_eq!(
+            renderer.render_frame(&camera, &scene),
+            renderer.render_frame(&camera, &scene)
+        );
+    }
+
+    #[test]
+    fn direct_reproducible_test() {
+        // The area light is sampled by the direct shader, which must draw from the pixel rng.
+        let light = Rect::new(
+            Point::new(-1., 3., 2.),
+            Vector::X * 2.,
+            Vector::Z * 4.,
+            Color::WHITE,
+            5.,
+        )
+        .with_samples(4);
+        let sphere = Sphere::new(Point::new(0., 0., 4.), 1.).with_material(Material::DEFAULT);
+        let scene = Scene::new(
+            vec![Box::new(sphere) as Box<dyn Object>],
+            vec![Box::new(light) as Box<dyn Light>],
+            0.,
+        );
+        let renderer = Renderer::default()
+            .with_integrator(integrator::Shading::new(vec![Box::new(shader::Direct)]));
+        let camera = camera();
+
+        let image = renderer.render_frame(&camera, &scene);
+        assert_eq!(image, renderer.render_frame(&camera, &scene));
+        assert!(image.pixels().any(|px| px.0[0] > 0));
+    }
+
+    #[test]
+    fn progressive_test() {
+        let renderer = Renderer::default();
+        let (camera, scene) = (camera(), scene());
+        let mut progressive = renderer.start_progressive(&camera);
+
+        // The first pass samples pixel centers, like a single sample frame.
+        renderer.render_pass(&mut progressive, &camera, &scene);
+        assert_eq!(progressive.passes(), 1);
+        assert_eq!(
+            renderer.develop(progressive.film()),
+            renderer.render_frame(&camera, &scene)
+        );
+
+        for _ in 0..3 {
+            renderer.render_pass(&mut progressive, &camera, &scene);
+        }
+        assert_eq!(progressive.passes(), 4);
+    }
+
+    #[test]
+    fn outside_pixel_test() {
+        let renderer = Renderer::new(Rgba::new(10, 128, 250, 255));
+        let pixel = renderer.render_pixel(8, 0, &camera(), &scene());
+
+        // The display color goes through gamma decoding and encoding.
+        let [r, g, b, a] = pixel.to_array();
+        assert!(r.abs_diff(10) <= 1 && g.abs_diff(128) <= 1 && b.abs_diff(250) <= 1);
+        assert_eq!(a, 255);
+    }
+
+    #[test]
+    fn region_test() {
+        let (camera, scene) = (camera(), scene());
+        let renderer = |adaptive: bool| {
+            let renderer = Renderer::default()
+                .with_sampler(Sampler::jittered(2))
+                .with_filter(Filter::gaussian(1.5));
+
+            if adaptive {
+                renderer.with_adaptive(Adaptive::new(0.05, 2))
+            } else {
+                renderer
+            }
+        };
+
+        for adaptive in [false, true] {
+            let full = renderer(adaptive).render_frame(&camera, &scene);
+            let region = Region::new(1, 3, 3, 4);
+
+            // Cropped regions are the same as the matching part of the full frame.
+            let cropped = renderer(adaptive)
+                .with_region(region.cropped())
+                .render_frame(&camera, &scene);
+            assert_eq!(cropped.dimensions(), (3, 4));
+            for (x, y, pixel) in cropped.enumerate_pixels() {
+                assert_eq!(pixel, full.get_pixel(x + 1, y + 3));
+            }
+
+            // Uncropped regions keep the full frame size, with transparent pixels around them.
+            let uncropped = renderer(adaptive)
+                .with_region(region)
+                .render_frame(&camera, &scene);
+            assert_eq!(uncropped.dimensions(), full.dimensions());
+            for (x, y, pixel) in uncropped.enumerate_pixels() {
+                if region.contains(x, y) {
+                    assert_eq!(pixel, full.get_pixel(x, y));
+                } else {
+                    assert_eq!(pixel.0, [0, 0, 0, 0]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//!
+//! Adaptive anti-aliasing.
+//!
+//! Most pixels of a frame are flat, and shooting many rays through them is a waste of time.
+//! Adaptive anti-aliasing first renders one sample per pixel, then only refines pixels whose color
+//! differs too much from their neighbors. Refined pixels are split in four quadrants, and each
+//! quadrant is recursively split again as long as its samples disagree.
+//!
+//! Contrast alone misses the noise of stochastic integrators, which is spread evenly over flat
+//! areas. When a maximum variance is set, a second sample is taken in each pixel, and pixels (and
+//! quadrants) whose samples variance exceeds it are refined as well.
+//!
+
+use crate::colors::Color;
+
+///
+/// Adaptive anti-aliasing settings.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adaptive {
+    /// Maximum color difference, per channel, between samples considered similar.
+    threshold: f32,
+    /// Maximum number of recursive subdivisions of a pixel.
+    max_depth: u32,
+    /// Maximum variance, per channel, of the samples of a pixel, or `None` to ignore variance.
+    variance: Option<f32>,
+}
+
+impl Adaptive {
+    ///
+    /// Create new adaptive anti-aliasing settings.
+    ///
+    /// `threshold` is compared to the largest channel difference between two linear colors.
+    /// `max_depth` bounds the subdivisions: a pixel gets at most `4^max_depth` samples.
+    ///
+    pub fn new(threshold: f32, max_depth: u32) -> Self {
+        Self {
+            threshold,
+            max_depth,
+            variance: None,
+        }
+    }
+
+    ///
+    /// Also refine pixels whose samples variance exceeds `variance`.
+    ///
+    /// Each pixel then gets at least two samples, to estimate its variance.
+    ///
+    pub fn with_variance(mut self, variance: f32) -> Self {
+        self.variance = Some(variance);
+        self
+    }
+
+    ///
+    /// Return the maximum variance, if variance driven refinement is enabled.
+    ///
+    pub fn variance(&self) -> Option<f32> {
+        self.variance
+    }
+
+    ///
+    /// Check whether two samples differ beyond the threshold.
+    ///
+    pub fn differ(&self, a: Color, b: Color) -> bool {
+        let diff = [a.r - b.r, a.g - b.g, a.b - b.b, a.a - b.a]
+            .into_iter()
+            .fold(0., |acc: f32, d| acc.max(d.abs()));
+
+        diff > self.threshold
+    }
+
+    ///
+    /// Check whether the variance of `samples` exceeds the maximum variance.
+    ///
+    /// The unbiased sample variance is computed per channel, and the largest one is compared.
+    /// Always `false` if variance driven refinement is disabled, or with less than two samples.
+    ///
+    pub fn noisy(&self, samples: &[Color]) -> bool {
+        let Some(max) = self.variance else {
+            return false
+        };
+        if samples.len() < 2 {
+            return false;
+        }
+
+        let n = samples.len() as f32;
+        let channels = |c: &Color| [c.r, c.g, c.b, c.a];
+        let mean = channels(&(samples.iter().fold(Color::TRANSPARENT, |acc, c| acc + *c) / n));
+        let var = samples
+            .iter()
+            .fold([0.; 4], |acc, c| {
+                let c = channels(c);
+                std::array::from_fn(|i| acc[i] + (c[i] - mean[i]).powi(2))
+            })
+            .into_iter()
+            .fold(0., f32::max)
+            / (n - 1.);
+
+        var > max
+    }
+
+    ///
+    /// Compute the color of a square area of the frame.
+    ///
+    /// `(x, y)` is the top-left corner of the area, `size` its side, and `center` the sample
+    /// already taken at its center. `sample` renders a single sample at a sub-pixel position.
+    ///
+    /// The area is split in four quadrants, which are sampled at their center. If these samples
+    /// agree with each other (and their variance is low enough, see [noisy](Self::noisy)), their
+    /// average is returned, otherwise each quadrant is refined in turn.
+    ///
+    pub fn refine(
+        &self,
+        (x, y): (f32, f32),
+        size: f32,
+        center: Color,
+        depth: u32,
+        sample: &mut impl FnMut(f32, f32) -> Color,
+    ) -> Color {
+        if depth >= self.max_depth {
+            return center;
+        }
+
+        let half = size / 2.;
+        let quadrants = [(0., 0.), (half, 0.), (0., half), (half, half)].map(|(dx, dy)| {
+            let corner = (x + dx, y + dy);
+            let color = sample(corner.0 + half / 2., corner.1 + half / 2.);
+
+            (corner, color)
+        });
+
+        let samples: [Color; 5] =
+            std::array::from_fn(|i| if i == 0 { center } else { quadrants[i - 1].1 });
+        let uniform =
+            quadrants.iter().all(|(_, c)| !self.differ(*c, center)) && !self.noisy(&samples);
+
+        let sum = if uniform {
+            quadrants
+                .iter()
+                .fold(Color::TRANSPARENT, |acc, (_, c)| acc + *c)
+        } else {
+            quadrants
+                .iter()
+                .fold(Color::TRANSPARENT, |acc, (corner, c)| {
+                    acc + self.refine(*corner, half, *c, depth + 1, sample)
+                })
+        };
+
+        sum / 4.
+    }
+}
+
+impl Default for Adaptive {
+    fn default() -> Self {
+        Self::new(0.05, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn differ_test() {
+        let adaptive = Adaptive::new(0.1, 2);
+        let grey = Color::new(0.5, 0.5, 0.5, 1.);
+
+        assert!(!adaptive.differ(grey, grey));
+        assert!(!adaptive.differ(grey, Color::new(0.55, 0.45, 0.5, 1.)));
+        assert!(adaptive.differ(grey, Color::new(0.5, 0.5, 0.7, 1.)));
+        assert!(adaptive.differ(grey, Color::new(0.5, 0.5, 0.5, 0.8)));
+    }
+
+    #[test]
+    fn noisy_test() {
+        let grey = Color::new(0.5, 0.5, 0.5, 1.);
+        let white = Color::new(1., 1., 1., 1.);
+
+        // Variance of [0.5, 1] is 0.125.
+        assert!(!Adaptive::new(0.1, 2).noisy(&[grey, white]));
+        assert!(Adaptive::new(0.1, 2)
+            .with_variance(0.1)
+            .noisy(&[grey, white]));
+        assert!(!Adaptive::new(0.1, 2)
+            .with_variance(0.2)
+            .noisy(&[grey, white]));
+        assert!(!Adaptive::new(0.1, 2).with_variance(0.).noisy(&[white]));
+    }
+
+    #[test]
+    fn flat_refine_test() {
+        let adaptive = Adaptive::new(0.1, 3);
+        let grey = Color::new(0.5, 0.5, 0.5, 1.);
+        let mut count = 0;
+
+        let color = adaptive.refine((0., 0.), 1., grey, 0, &mut |_, _| {
+            count += 1;
+            grey
+        });
+
+        // Samples agree, so only the four quadrants are sampled.
+        assert_eq!(count, 4);
+        assert!(!adaptive.differ(color, grey));
+    }
+
+    #[test]
+    fn edge_refine_test() {
+        let adaptive = Adaptive::new(0.1, 3);
+        let white = Color::new(1., 1., 1., 1.);
+        let black = Color::new(0., 0., 0., 1.);
+        // Vertical edge at a third of the pixel.
+        let sample = |x: f32, _| if x < 1. / 3. { white } else { black };
+        let mut count = 0;
+
+        let color = adaptive.refine((0., 0.), 1., black, 0, &mut |x, y| {
+            count += 1;
+            sample(x, y)
+        });
+
+        // Only the quadrants crossed by the edge are refined, down to an eighth of a pixel.
+        assert!(count > 4 && count < 4 + 16 + 64);
+        assert!((color.r - 3. / 8.).abs() <= 1e-4);
+        assert_eq!(color.a, 1.);
+    }
+
+    #[test]
+    fn max_depth_test() {
+        let white = Color::new(1., 1., 1., 1.);
+        let black = Color::new(0., 0., 0., 1.);
+        let mut count = 0;
+
+        let color = Adaptive::new(0.1, 0).refine((0., 0.), 1., black, 0, &mut |_, _| {
+            count += 1;
+            white
+        });
+
+        assert_eq!(count, 0);
+        assert_eq!(color, black);
+    }
+
+    #[test]
+    fn noisy_refine_test() {
+        let grey = Color::new(0.5, 0.5, 0.5, 1.);
+        // Samples alternate within the contrast threshold, but with some variance.
+        let mut flip = false;
+        let mut noise = |_, _| {
+            flip = !flip;
+            if flip {
+                Color::new(0.45, 0.45, 0.45, 1.)
+            } else {
+                Color::new(0.55, 0.55, 0.55, 1.)
+            }
+        };
+
+        let mut count = 0;
+        Adaptive::new(0.1, 2).refine((0., 0.), 1., grey, 0, &mut |x, y| {
+            count += 1;
+            noise(x, y)
+        });
+        assert_eq!(count, 4);
+
+        let mut count = 0;
+        Adaptive::new(0.1, 2)
+            .with_variance(1e-3)
+            .refine((0., 0.), 1., grey, 0, &mut |x, y| {
+                count += 1;
+                noise(x, y)
+            });
+        assert_eq!(count, 4 + 16);
+    }
+}
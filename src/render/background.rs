@@ -0,0 +1,127 @@
+//!
+//! Scene backgrounds.
+//!
+//! The [Background] gives the light coming from rays which don't hit any object, depending on
+//! their direction. It can be a solid color, a vertical gradient, or an analytic daylight [Sky].
+//!
+
+pub mod sky;
+
+pub use sky::Sky;
+
+use crate::{
+    colors::{Color, Rgba},
+    maths::Vector,
+};
+
+use super::shader::GammaEncoder;
+
+///
+/// Light coming from rays escaping the scene.
+///
+/// Colors are in linear space.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Background {
+    /// Same color in all directions.
+    Solid(Color),
+    /// Vertical gradient, from `bottom` for rays going down to `top` for rays going up.
+    Gradient { bottom: Color, top: Color },
+    /// Analytic daylight sky.
+    Sky(Sky),
+}
+
+impl Background {
+    pub fn solid(color: Color) -> Self {
+        Self::Solid(color)
+    }
+
+    pub fn gradient(bottom: Color, top: Color) -> Self {
+        Self::Gradient { bottom, top }
+    }
+
+    ///
+    /// Return the light coming from the direction `dir`.
+    ///
+    pub fn color(&self, dir: Vector) -> Color {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Gradient { bottom, top } => {
+                let t = (dir.normalize().y + 1.) / 2.;
+
+                *bottom * (1. - t) + *top * t
+            }
+            Self::Sky(sky) => sky.color(dir),
+        }
+    }
+}
+
+impl From<Color> for Background {
+    fn from(color: Color) -> Self {
+        Self::Solid(color)
+    }
+}
+
+impl From<Rgba> for Background {
+    ///
+    /// [Rgba] colors are display colors, so they are gamma decoded to a linear solid background.
+    ///
+    fn from(color: Rgba) -> Self {
+        Self::Solid(GammaEncoder::default().decode(color.into()))
+    }
+}
+
+impl From<Sky> for Background {
+    fn from(sky: Sky) -> Self {
+        Self::Sky(sky)
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self::Solid(Color::BLACK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solid_test() {
+        let color = Color::new(0.1, 0.2, 0.3, 1.);
+        let background = Background::from(color);
+
+        assert_eq!(background, Background::solid(color));
+        assert_eq!(background.color(Vector::Y), color);
+        assert_eq!(background.color(Vector::new(1., -2., 3.)), color);
+    }
+
+    #[test]
+    fn gradient_test() {
+        let (bottom, top) = (Color::new(1., 0., 0., 1.), Color::new(0., 0., 1., 1.));
+        let background = Background::gradient(bottom, top);
+
+        assert_eq!(background.color(-Vector::Y), bottom);
+        assert_eq!(background.color(Vector::Y * 3.), top);
+        assert_eq!(background.color(Vector::X), Color::new(0.5, 0., 0.5, 1.));
+    }
+
+    #[test]
+    fn rgba_test() {
+        let background = Background::from(Rgba::new(0, 255, 128, 255));
+        let Background::Solid(color) = background else {
+            panic!("expected a solid background")
+        };
+
+        assert_eq!((color.r, color.g, color.a), (0., 1., 1.));
+        assert!((color.b - (128. / 255f32).powf(2.2)).abs() <= 1e-5);
+    }
+
+    #[test]
+    fn sky_test() {
+        let sky = Sky::new(Vector::new(0., 1., 1.), 3.);
+
+        assert_eq!(Background::from(sky).color(Vector::Y), sky.color(Vector::Y));
+    }
+}
@@ -0,0 +1,242 @@
+//!
+//! Analytic daylight sky.
+//!
+//! The sky color is given by the Preetham model (A. J. Preetham, P. Shirley and B. Smits, "A
+//! Practical Analytic Model for Daylight", 1999). The luminance and chromaticity of each direction
+//! are computed from the position of the sun and the turbidity of the atmosphere, i.e. the amount
+//! of haze: `2` is a very clear sky, and `10` a hazy one.
+//!
+//! The sun itself is not part of the sky, but can be added to the scene as a
+//! [Directional](crate::scene::lights::Directional) light with [Sky::sun].
+//!
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use crate::{colors::Color, maths::Vector, scene::lights::Directional};
+
+///
+/// Preetham daylight sky.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+    /// Unit vector pointing toward the sun.
+    sun_dir: Vector,
+    turbidity: f32,
+    /// Scale applied to the sky luminance, expressed in kcd/m².
+    intensity: f32,
+}
+
+impl Sky {
+    ///
+    /// Create a new sky, with the sun in the direction `sun_dir`.
+    ///
+    pub fn new(sun_dir: Vector, turbidity: f32) -> Self {
+        Self {
+            sun_dir: sun_dir.normalize(),
+            turbidity,
+            intensity: 0.05,
+        }
+    }
+
+    ///
+    /// Set the scale applied to the sky luminance, which is expressed in kcd/m².
+    ///
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn sun_dir(&self) -> Vector {
+        self.sun_dir
+    }
+
+    ///
+    /// Build a directional light for the sun, of the given `intensity`.
+    ///
+    /// The sun color is attenuated by the atmosphere, turning orange and dim near the horizon.
+    ///
+    pub fn sun(&self, intensity: f32) -> Directional {
+        let theta = self.sun_theta();
+
+        // Relative optical air mass, i.e. length of the path of sunlight through the atmosphere.
+        let mass = if theta >= FRAC_PI_2 {
+            f32::INFINITY
+        } else {
+            (theta.cos() + 0.15 * (93.885 - theta.to_degrees()).powf(-1.253)).recip()
+        };
+
+        // Rayleigh scattering, and aerosols scattering following Ångström's formula.
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let transmittance =
+            |rayleigh: f32, lambda: f32| (-mass * (rayleigh + beta * lambda.powf(-1.3))).exp();
+
+        let color = Color::from_rgb(
+            transmittance(0.046, 0.680),
+            transmittance(0.108, 0.550),
+            transmittance(0.265, 0.440),
+        );
+
+        Directional::new(-self.sun_dir, color, intensity)
+    }
+
+    ///
+    /// Return the light coming from the sky in the direction `dir`.
+    ///
+    /// Directions below the horizon get the color of the horizon.
+    ///
+    pub fn color(&self, dir: Vector) -> Color {
+        let dir = dir.normalize();
+        let theta = dir.y.clamp(0.001, 1.).acos();
+        let gamma = dir.dot(self.sun_dir).clamp(-1., 1.).acos();
+
+        let theta_s = self.sun_theta();
+        let t = self.turbidity;
+
+        let (lum_z, x_z, y_z) = self.zenith();
+        let perez =
+            |coefs: [f32; 5]| Self::perez(theta, gamma, coefs) / Self::perez(0., theta_s, coefs);
+
+        let lum = lum_z
+            * perez([
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ]);
+        let x = x_z
+            * perez([
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ]);
+        let y = y_z
+            * perez([
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ]);
+
+        Self::xyy_to_rgb(x, y, lum * self.intensity)
+    }
+
+    ///
+    /// Angle between the sun and the zenith, clamped to the horizon.
+    ///
+    fn sun_theta(&self) -> f32 {
+        self.sun_dir.y.clamp(-1., 1.).acos().min(FRAC_PI_2)
+    }
+
+    ///
+    /// Luminance and chromaticity `(Y, x, y)` of the zenith.
+    ///
+    fn zenith(&self) -> (f32, f32, f32) {
+        let (t, t2) = (self.turbidity, self.turbidity * self.turbidity);
+        let th = self.sun_theta();
+        let (th2, th3) = (th * th, th * th * th);
+
+        let chi = (4. / 9. - t / 120.) * (PI - 2. * th);
+        let lum = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.);
+
+        let x = t2 * (0.00166 * th3 - 0.00375 * th2 + 0.00209 * th)
+            + t * (-0.02903 * th3 + 0.06377 * th2 - 0.03202 * th + 0.00394)
+            + (0.11693 * th3 - 0.21196 * th2 + 0.06052 * th + 0.25886);
+        let y = t2 * (0.00275 * th3 - 0.00610 * th2 + 0.00317 * th)
+            + t * (-0.04214 * th3 + 0.08970 * th2 - 0.04153 * th + 0.00516)
+            + (0.15346 * th3 - 0.26756 * th2 + 0.06670 * th + 0.26688);
+
+        (lum, x, y)
+    }
+
+    ///
+    /// Perez sky luminance distribution, for a direction at an angle `theta` from the zenith and
+    /// `gamma` from the sun.
+    ///
+    fn perez(theta: f32, gamma: f32, [a, b, c, d, e]: [f32; 5]) -> f32 {
+        (1. + a * (b / theta.cos()).exp()) * (1. + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+    }
+
+    ///
+    /// Convert a color from the CIE xyY color space to linear sRGB.
+    ///
+    fn xyy_to_rgb(x: f32, y: f32, lum: f32) -> Color {
+        if y <= 0. {
+            return Color::BLACK;
+        }
+
+        let (cx, cy, cz) = (x / y * lum, lum, (1. - x - y) / y * lum);
+
+        Color::from_rgb(
+            (3.2406 * cx - 1.5372 * cy - 0.4986 * cz).max(0.),
+            (-0.9689 * cx + 1.8758 * cy + 0.0415 * cz).max(0.),
+            (0.0557 * cx - 0.2040 * cy + 1.0570 * cz).max(0.),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{maths::Point, scene::Light};
+
+    fn luminance(c: Color) -> f32 {
+        0.2126 * c.r + 0.7152 * c.g + 0.0722 * c.b
+    }
+
+    #[test]
+    fn zenith_test() {
+        // The Perez distribution is normalized at the zenith.
+        let sky = Sky::new(Vector::new(1., 1., 0.), 3.).with_intensity(1.);
+        let (lum, _, _) = sky.zenith();
+        let zenith = sky.color(Vector::Y);
+
+        assert!((luminance(zenith) - lum).abs() <= 1e-2 * lum);
+        // A clear sky is blue.
+        assert!(zenith.b > zenith.r);
+    }
+
+    #[test]
+    fn sun_glow_test() {
+        let sky = Sky::new(Vector::new(1., 0.5, 0.), 3.);
+        let toward = luminance(sky.color(Vector::new(1., 0.6, 0.1)));
+        let away = luminance(sky.color(Vector::new(-1., 0.6, -0.1)));
+
+        assert!(toward > 2. * away);
+    }
+
+    #[test]
+    fn turbidity_test() {
+        // Haze washes out the blue of the sky.
+        let sun = Vector::new(0., 1., 1.);
+        let ratio = |t| {
+            let c = Sky::new(sun, t).color(Vector::new(0., 0.8, -1.));
+            c.b / c.r
+        };
+
+        assert!(ratio(2.) > ratio(8.));
+    }
+
+    #[test]
+    fn sun_test() {
+        let color = |sun_dir| {
+            let sun = Sky::new(sun_dir, 3.).sun(1.);
+            let s = sun.sample(Point::ORIGIN, (0.5, 0.5)).unwrap();
+
+            assert!((s.dir - sun_dir.normalize()).magn() <= 1e-5);
+            s.radiance
+        };
+
+        let noon = color(Vector::new(0., 1., 0.2));
+        let sunset = color(Vector::new(0., 0.05, 1.));
+
+        assert!(noon.r <= 1. && noon.r > 0.8 && noon.b > 0.5);
+        assert!(sunset.r < noon.r && sunset.b < sunset.r * 0.5);
+        assert_eq!(color(Vector::new(0., -0.5, 1.)).r, 0.);
+    }
+}
@@ -6,16 +6,31 @@
 //! [objects](crate::scene::objects), and how [lights](crate::scene::lights) would affect said
 //! objects.
 //!
+//! Cameras are pinholes by default, with everything in focus. A thin [Lens] gives them depth of
+//! field. Other [Projections](Projection) can replace the perspective one, e.g. orthographic
+//! views for technical drawings, or panoramas.
+//!
+//! The orientation of a camera is built from its looking direction and an up vector, so that the
+//! horizon stays level: the sensor `x` axis is horizontal, and its `y` axis points upward as much
+//! as possible. Cameras can be moved or orbited around a target without being rebuilt.
+//!
 
 use std::fmt::{self, Display};
 
 use crate::maths::{Point, Quaternion, Vector};
 use crate::render::Ray;
 
+/// Highest elevation reached when orbiting, just below the poles.
+const MAX_ELEVATION: f32 = 89.9 * std::f32::consts::PI / 180.;
+
 pub mod fov;
+pub mod lens;
+pub mod projection;
 pub mod sensor;
 
 pub use fov::Fov;
+pub use lens::Lens;
+pub use projection::{FisheyeMapping, Projection};
 pub use sensor::Sensor;
 
 ///
@@ -29,12 +44,18 @@ pub struct Camera {
     pos: Point,
     /// Looking direction of the camera.
     dir: Vector,
+    /// World space up direction, which the camera keeps level with.
+    up: Vector,
     /// Render screen.
     sensor: Sensor,
     /// Camera field of view.
     fov: Fov,
-    /// Rotation around the `dir` axis.
+    /// Rotation around the `dir` axis, in radians.
     tilt: f32,
+    /// Thin lens, `None` for a pinhole camera.
+    lens: Option<Lens>,
+    /// Mapping from the sensor to rays.
+    projection: Projection,
 
     /// Distance from the camera to the screen.
     dist: f32,
@@ -51,37 +72,297 @@ impl Camera {
     /// `sensor` Camera's [Sensor].
     /// `fov` the distance between the camera and the screen is computed from the camera Field of
     /// view.
-    /// `tilt` an arbirary rotation, using the `dir` vector as an axis.
+    /// `tilt` an arbirary rotation, using the `dir` vector as an axis, in angular degree.
+    ///
+    /// The camera is kept level with the `Y` axis, before being tilted.
     ///
     pub fn new(pos: Point, dir: Vector, sensor: Sensor, fov: Fov, tilt: f32) -> Self {
-        let dir = dir.normalize();
-        let tilt = tilt.to_radians();
-
-        let dist = sensor.distance(fov);
-
-        let mut base_rot = Quaternion::from_arc(Vector::Z, dir);
-        base_rot *= Quaternion::from_z_rot(tilt.to_radians());
-
-        Camera {
+        let mut camera = Camera {
             pos,
-            dir,
+            dir: dir.normalize(),
+            up: Vector::Y,
             sensor,
             fov,
-            tilt,
-            dist,
-            base_rot,
+            tilt: tilt.to_radians(),
+            lens: None,
+            projection: Projection::Perspective,
+            dist: sensor.distance(fov),
+            base_rot: Quaternion::IDENTITY,
+        };
+
+        camera.orient();
+        camera
+    }
+
+    ///
+    /// Build a new camera at `eye`, looking at `target`, and kept level with the `up` direction.
+    ///
+    /// The camera has the default [Sensor] and [Fov].
+    ///
+    /// # Panics
+    /// Panics if `eye` and `target` are the same point, if `up` is null, or if `up` is collinear
+    /// with the view direction, which would leave the roll of the camera undetermined.
+    ///
+    pub fn look_at(eye: Point, target: Point, up: Vector) -> Self {
+        assert!(
+            eye != target,
+            "Camera::look_at: eye and target are the same point."
+        );
+        assert!(up.magn2() > 0., "Camera::look_at: up is a null vector.");
+        assert!(
+            up.normalize().cross((target - eye).normalize()).magn2() > 1e-12,
+            "Camera::look_at: up and the view direction are collinear."
+        );
+
+        let mut camera = Self {
+            pos: eye,
+            dir: (target - eye).normalize(),
+            up: up.normalize(),
+            ..Self::default()
+        };
+
+        camera.orient();
+        camera
+    }
+
+    ///
+    /// Set the camera [Sensor].
+    ///
+    pub fn with_sensor(mut self, sensor: Sensor) -> Self {
+        self.sensor = sensor;
+        self.dist = sensor.distance(self.fov);
+        self
+    }
+
+    ///
+    /// Set the camera [Fov].
+    ///
+    pub fn with_fov(mut self, fov: Fov) -> Self {
+        self.fov = fov;
+        self.dist = self.sensor.distance(fov);
+        self
+    }
+
+    ///
+    /// Set the rotation around the looking direction, in angular degree.
+    ///
+    pub fn with_tilt(mut self, tilt: f32) -> Self {
+        self.tilt = tilt.to_radians();
+        self.orient();
+        self
+    }
+
+    ///
+    /// Set a thin [Lens], giving depth of field.
+    ///
+    pub fn with_lens(mut self, lens: Lens) -> Self {
+        self.lens = Some(lens);
+        self
+    }
+
+    ///
+    /// Set the camera [Projection].
+    ///
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    ///
+    /// Move the camera to `pos`, keeping its orientation.
+    ///
+    pub fn move_to(&mut self, pos: Point) {
+        self.pos = pos;
+    }
+
+    ///
+    /// Move the camera by `offset`, keeping its orientation.
+    ///
+    pub fn translate(&mut self, offset: Vector) {
+        self.pos += offset;
+    }
+
+    ///
+    /// Turn the camera toward `target`, keeping its position.
+    ///
+    pub fn point_at(&mut self, target: Point) {
+        let dir = target - self.pos;
+
+        if dir.magn2() > 0. {
+            self.dir = dir.normalize();
+            self.orient();
+        }
+    }
+
+    ///
+    /// Orbit the camera around `target`, by `yaw` angular degrees around the up direction, then
+    /// `pitch` angular degrees upward. The camera then looks at `target`.
+    ///
+    /// The pitch is clamped so that the camera never goes over the poles, where it would flip.
+    ///
+    pub fn orbit(&mut self, target: Point, yaw: f32, pitch: f32) {
+        let offset = self.pos - target;
+        let dist = offset.magn();
+        if dist == 0. {
+            return;
         }
+
+        let offset = Quaternion::from_rotation(yaw.to_radians(), self.up) * (offset / dist);
+        let elevation = self.up.dot(offset).clamp(-1., 1.).asin();
+        let pitch = (elevation + pitch.to_radians()).clamp(-MAX_ELEVATION, MAX_ELEVATION);
+
+        let horizontal = offset - self.up * self.up.dot(offset);
+        let horizontal = if horizontal.magn2() > 0. {
+            horizontal.normalize()
+        } else {
+            self.up.any_orthonormal()
+        };
+
+        self.pos = target + (horizontal * pitch.cos() + self.up * pitch.sin()) * dist;
+        self.point_at(target);
+    }
+
+    ///
+    /// Returns the camera's position.
+    ///
+    pub fn pos(&self) -> Point {
+        self.pos
+    }
+
+    ///
+    /// Returns the camera's looking direction.
+    ///
+    pub fn dir(&self) -> Vector {
+        self.dir
+    }
+
+    ///
+    /// Returns the camera's [Sensor].
+    ///
+    pub fn sensor(&self) -> &Sensor {
+        &self.sensor
     }
 
     ///
-    /// Returns the [Ray](crate::render::rays::Ray) that would hit a given pixel of the `Camera`'s
-    /// `Sensor`.
+    /// Returns the camera's [Lens], if any.
+    ///
+    pub fn lens(&self) -> Option<&Lens> {
+        self.lens.as_ref()
+    }
+
+    ///
+    /// Returns the camera's [Projection].
+    ///
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    ///
+    /// Returns the [Ray](crate::render::rays::Ray) that would hit the center of a given pixel of
+    /// the `Camera`'s `Sensor`.
     ///
     pub fn get_ray(&self, x: u32, y: u32) -> Option<Ray> {
-        self.sensor.pixel_pos_to_render_pos(x, y).map(|(x, y)| {
-            let dir = Vector::new(x, y, 0.) + self.dist * Vector::Z;
-            Ray::new(self.pos, (self.base_rot * dir).normalize())
-        })
+        self.get_subpixel_ray(x as f32 + 0.5, y as f32 + 0.5)
+    }
+
+    ///
+    /// Returns the [Ray](crate::render::rays::Ray) going through a given point of the `Camera`'s
+    /// `Sensor`, expressed in pixels.
+    ///
+    /// Integer coordinates are pixel corners, so `(x + 0.5, y + 0.5)` is the center of the pixel
+    /// `(x, y)`.
+    ///
+    /// The ray leaves from the center of the [Lens], if any.
+    ///
+    pub fn get_subpixel_ray(&self, x: f32, y: f32) -> Option<Ray> {
+        self.ray(x, y, None)
+    }
+
+    ///
+    /// Returns the [Ray](crate::render::rays::Ray) going through a given point of the `Camera`'s
+    /// `Sensor`, expressed in pixels, and leaving from a point of the [Lens].
+    ///
+    /// `lens` is a pair of uniform random numbers in `[0, 1)`, from which the point on the lens
+    /// is drawn. It is ignored by pinhole cameras.
+    ///
+    pub fn get_lens_ray(&self, x: f32, y: f32, lens: (f32, f32)) -> Option<Ray> {
+        self.ray(x, y, Some(lens))
+    }
+
+    ///
+    /// Ray going through the sub-pixel position `(x, y)`, leaving from the point of the [Lens]
+    /// drawn from `lens`, or from its center if `None`.
+    ///
+    fn ray(&self, x: f32, y: f32, lens: Option<(f32, f32)>) -> Option<Ray> {
+        let (rx, ry) = self.sensor.subpixel_pos_to_render_pos(x, y)?;
+
+        match self.projection {
+            Projection::Perspective => Some(self.perspective_ray(rx, ry, lens)),
+            Projection::Orthographic { width } => Some(self.orthographic_ray(rx, ry, width)),
+            _ => {
+                let (dir, spread) = self.projection.panoramic_dir(x, y, &self.sensor)?;
+
+                Some(Ray::new(self.pos, (self.base_rot * dir).normalize()).with_spread(spread))
+            }
+        }
+    }
+
+    ///
+    /// Compute the rotation applied to rays leaving the camera, from its direction, up vector and
+    /// tilt.
+    ///
+    /// The camera basis is orthonormalized from the up vector. When looking along the up vector,
+    /// any horizontal vector is used instead, which only changes the roll of the camera.
+    ///
+    fn orient(&mut self) {
+        let right = self.up.cross(self.dir);
+        let right = if right.magn2() > 1e-12 {
+            right.normalize()
+        } else {
+            self.dir.any_orthonormal()
+        };
+        let up = self.dir.cross(right);
+
+        self.base_rot = Quaternion::from_basis(right, up, self.dir);
+        self.base_rot *= Quaternion::from_z_rot(self.tilt);
+    }
+
+    ///
+    /// Perspective ray going through the render position `(x, y)` of the sensor.
+    ///
+    fn perspective_ray(&self, x: f32, y: f32, lens: Option<(f32, f32)>) -> Ray {
+        let dir = Vector::new(x, y, 0.) + self.dist * Vector::Z;
+        // A pixel is one unit wide on the screen, at `dist` from the camera.
+        let spread = self.dist.recip();
+
+        // The center of the lens behaves like a pinhole.
+        let (Some(thin), Some(lens)) = (self.lens, lens) else {
+            return Ray::new(self.pos, (self.base_rot * dir).normalize()).with_spread(spread)
+        };
+
+        // Rays through the lens converge where the pinhole ray meets the focal plane.
+        let focus = dir * (thin.focus() / self.dist);
+        let (lx, ly) = thin.sample(lens);
+        let offset = Vector::new(lx, ly, 0.);
+
+        Ray::new(
+            self.pos + self.base_rot * offset,
+            (self.base_rot * (focus - offset)).normalize(),
+        )
+        .with_spread(spread)
+    }
+
+    ///
+    /// Orthographic ray leaving from the render position `(x, y)` of the sensor, mapped to a
+    /// rectangle `width` world units wide.
+    ///
+    /// Parallel rays don't spread, so textures are sampled at their full resolution.
+    ///
+    fn orthographic_ray(&self, x: f32, y: f32, width: f32) -> Ray {
+        let scale = width / self.sensor.render_size().0;
+        let offset = Vector::new(x * scale, y * scale, 0.);
+
+        Ray::new(self.pos + self.base_rot * offset, self.dir)
     }
 }
 
@@ -113,9 +394,158 @@ impl Display for Camera {
             dir: {},
             sensor: {},
             fov: {},
-            tilt: {}
+            tilt: {},
+            projection: {}
         }}",
-            self.pos, self.dir, self.sensor, self.fov, self.tilt
+            self.pos, self.dir, self.sensor, self.fov, self.tilt, self.projection
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new(
+            Point::new(1., 2., -3.),
+            Vector::new(0.3, -0.2, 1.),
+            Sensor::new(16, 12),
+            Fov::default(),
+            15.,
+        )
+    }
+
+    ///
+    /// Return the point where `ray` crosses the plane at `distance` in front of `camera`.
+    ///
+    fn at_distance(camera: &Camera, ray: Ray, distance: f32) -> Point {
+        let t =
+            (distance - (ray.orig - camera.pos()).dot(camera.dir())) / ray.dir.dot(camera.dir());
+        ray.orig + ray.dir * t
+    }
+
+    #[test]
+    fn lens_focus_test() {
+        let focus = 7.;
+        let camera = camera().with_lens(Lens::new(0.5, focus).with_blades(5));
+
+        for (x, y) in [(8., 6.), (0.5, 11.25), (13.7, 2.1)] {
+            let pinhole = camera.get_subpixel_ray(x, y).unwrap();
+            let target = at_distance(&camera, pinhole, focus);
+
+            // Rays leave from the lens plane, and meet on the focal plane.
+            for lens in [(0.1, 0.9), (0.5, 0.5), (0.75, 0.2), (0.99, 0.)] {
+                let ray = camera.get_lens_ray(x, y, lens).unwrap();
+
+                assert!((ray.orig - camera.pos()).dot(camera.dir()).abs() <= 1e-5);
+                assert!((ray.orig - camera.pos()).magn() <= 0.5 + 1e-5);
+                assert!((at_distance(&camera, ray, focus) - target).magn() <= 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn lens_center_test() {
+        // The center of an odd polygon isn't drawn from `(0.5, 0.5)`, but the pinhole ray still
+        // leaves from the center of the lens.
+        let lens = Lens::new(0.5, 7.).with_blades(5);
+        let camera = camera().with_lens(lens);
+
+        assert_ne!(lens.sample((0.5, 0.5)), (0., 0.));
+        assert_eq!(
+            camera.get_subpixel_ray(3.5, 4.5).unwrap().orig,
+            camera.pos()
+        );
+    }
+
+    #[test]
+    fn orthographic_test() {
+        let camera = camera().with_projection(Projection::orthographic(4.));
+
+        // Rays are all parallel to the camera direction, and leave from the camera plane.
+        let rays = [(0., 0.), (8., 0.), (0., 6.), (8., 6.), (3.2, 9.7)]
+            .map(|(x, y)| camera.get_subpixel_ray(x, y).unwrap());
+        for ray in rays {
+            assert!((ray.dir - camera.dir()).magn() <= 1e-6);
+            assert!((ray.orig - camera.pos()).dot(camera.dir()).abs() <= 1e-5);
+        }
+
+        // They span `width` world units horizontally, and follow the sensor aspect ratio: the
+        // border is half the width away from the center.
+        assert!(((rays[1].orig - rays[0].orig).magn() - 2.).abs() <= 1e-5);
+        assert!(((rays[2].orig - rays[0].orig).magn() - 1.5).abs() <= 1e-5);
+        assert!((rays[3].orig - camera.pos()).magn() <= 1e-5);
+    }
+
+    #[test]
+    fn look_at_test() {
+        let target = Point::new(2., 1., 5.);
+        let camera = Camera::look_at(Point::new(-1., 3., 0.), target, Vector::Y);
+
+        // The camera looks at the target, with its right vector kept horizontal.
+        assert!((camera.dir() - (target - camera.pos()).normalize()).magn() <= 1e-6);
+        let right = camera.base_rot * Vector::X;
+        assert!(right.dot(Vector::Y).abs() <= 1e-6);
+        assert!(right.dot(camera.dir()).abs() <= 1e-6);
+
+        // The center of the sensor looks at the target.
+        let ray = camera.get_subpixel_ray(256., 256.).unwrap();
+        assert!((ray.dir - camera.dir()).magn() <= 1e-5);
+    }
+
+    #[test]
+    fn point_at_up_test() {
+        // Looking along the up vector only leaves the roll of the camera arbitrary.
+        for target in [Point::new(0., 5., 0.), Point::new(0., -5., 0.)] {
+            let mut camera = Camera::look_at(Point::ORIGIN, Point::new(0., 0., 1.), Vector::Y);
+            camera.point_at(target);
+
+            for (x, y) in [(0., 0.), (256., 256.), (511.5, 100.)] {
+                let ray = camera.get_subpixel_ray(x, y).unwrap();
+                assert!(ray.dir.is_normalized(), "{ray:?}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Camera::look_at: eye and target are the same point.")]
+    fn look_at_same_point_test() {
+        Camera::look_at(Point::ORIGIN, Point::ORIGIN, Vector::Y);
+    }
+
+    #[test]
+    #[should_panic(expected = "Camera::look_at: up is a null vector.")]
+    fn look_at_null_up_test() {
+        Camera::look_at(Point::ORIGIN, Point::new(0., 0., 1.), Vector::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "Camera::look_at: up and the view direction are collinear.")]
+    fn look_at_collinear_up_test() {
+        Camera::look_at(Point::ORIGIN, Point::new(0., -5., 0.), Vector::Y);
+    }
+
+    #[test]
+    fn orbit_test() {
+        let target = Point::new(1., 0., 2.);
+        let mut camera = Camera::look_at(Point::new(1., 1., -2.), target, Vector::Y);
+        let dist = (camera.pos() - target).magn();
+
+        for (yaw, pitch) in [(30., 10.), (-120., 0.), (0., 200.), (45., -400.)] {
+            camera.orbit(target, yaw, pitch);
+            let offset = camera.pos() - target;
+
+            // The camera stays at the same distance, looking at the target, without going over
+            // the poles.
+            assert!((offset.magn() - dist).abs() <= 1e-4);
+            assert!((camera.dir() + offset.normalize()).magn() <= 1e-5);
+            assert!(offset.normalize().dot(Vector::Y).abs() <= MAX_ELEVATION.sin() + 1e-6);
+        }
+
+        // Large pitches are clamped.
+        camera.orbit(target, 0., 200.);
+        let elevation = (camera.pos() - target).normalize().dot(Vector::Y).asin();
+        assert!((elevation - MAX_ELEVATION).abs() <= 1e-4);
+    }
+}
@@ -18,8 +18,13 @@ use std::fmt::Display;
 ///
 #[derive(Clone, Copy, Debug)]
 pub enum Fov {
+    /// Horizontal angle of view, in angular degree.
     Horizontal(f32),
+    /// Vertical angle of view, in angular degree.
     Vertical(f32),
+    /// Focal length of the camera lens, in millimeters. The angles of view follow from the size
+    /// of the [Sensor](super::Sensor) film.
+    Focal(f32),
 }
 
 impl Fov {
@@ -40,6 +45,15 @@ impl Fov {
     pub const fn vert(degree: f32) -> Self {
         Self::Vertical(degree)
     }
+
+    ///
+    /// Create a new Fov from the focal length of the lens.
+    ///
+    /// `focal` is expressed in millimeters, like the film of the [Sensor](super::Sensor).
+    ///
+    pub const fn focal(focal: f32) -> Self {
+        Self::Focal(focal)
+    }
 }
 
 impl Default for Fov {
@@ -56,6 +70,7 @@ impl Display for Fov {
         match self {
             Self::Horizontal(angle) => f.write_fmt(format_args!("{} H", angle)),
             Self::Vertical(angle) => f.write_fmt(format_args!("{} V", angle)),
+            Self::Focal(focal) => f.write_fmt(format_args!("{}mm", focal)),
         }
     }
 }
@@ -0,0 +1,160 @@
+//!
+//! [Camera](super::Camera)'s Lens.
+//!
+//! A pinhole camera has everything in focus. A thin [Lens] instead gathers light over a disk of
+//! radius the aperture: only the points on the focal plane, at the focus distance from the
+//! camera, are sharp, and the others are blurred into the shape of the aperture, i.e. the bokeh.
+//!
+//! Real apertures are made of blades, and give polygonal bokehs.
+//!
+
+use std::f32::consts::PI;
+
+use crate::maths::sampling;
+
+///
+/// Thin lens.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lens {
+    /// Radius of the aperture, in world units.
+    aperture: f32,
+    /// Distance from the camera to the focal plane, along the looking direction.
+    focus: f32,
+    /// Number of aperture blades, `0` for a circular aperture.
+    blades: u32,
+    /// Rotation of the aperture polygon, in radians.
+    rotation: f32,
+}
+
+impl Lens {
+    ///
+    /// Create a new circular `Lens`, of radius `aperture`, focused at the distance `focus`.
+    ///
+    /// # Panics
+    /// Panics if `aperture` is negative, or if `focus` is not strictly positive.
+    ///
+    pub fn new(aperture: f32, focus: f32) -> Self {
+        assert!(aperture >= 0., "Lens::new: aperture must be positive.");
+        assert!(focus > 0., "Lens::new: focus must be strictly positive.");
+
+        Self {
+            aperture,
+            focus,
+            blades: 0,
+            rotation: 0.,
+        }
+    }
+
+    ///
+    /// Use a regular polygonal aperture of `blades` sides, giving polygonal bokehs.
+    ///
+    /// Less than 3 blades give a circular aperture.
+    ///
+    pub fn with_blades(mut self, blades: u32) -> Self {
+        self.blades = if blades < 3 { 0 } else { blades };
+        self
+    }
+
+    ///
+    /// Rotate the aperture polygon, by an angle expressed in angular degree.
+    ///
+    pub fn with_rotation(mut self, degree: f32) -> Self {
+        self.rotation = degree.to_radians();
+        self
+    }
+
+    ///
+    /// Return the radius of the aperture.
+    ///
+    pub fn aperture(&self) -> f32 {
+        self.aperture
+    }
+
+    ///
+    /// Return the distance to the focal plane.
+    ///
+    pub fn focus(&self) -> f32 {
+        self.focus
+    }
+
+    ///
+    /// Draw a point uniformly on the aperture, from a pair of uniform random numbers in `[0, 1)`.
+    ///
+    /// The point is expressed in the lens plane, in world units.
+    ///
+    pub fn sample(&self, (u, v): (f32, f32)) -> (f32, f32) {
+        let (x, y) = if self.blades == 0 {
+            sampling::concentric_disk((u, v))
+        } else {
+            // Pick one of the triangles joining the center to a side, then a point inside it.
+            let n = self.blades as f32;
+            let side = (u * n).floor().min(n - 1.);
+            let u = u * n - side;
+
+            let angle = |i: f32| self.rotation + 2. * PI * i / n;
+            let (a, b) = (angle(side), angle(side + 1.));
+            let r = u.sqrt();
+
+            (
+                r * ((1. - v) * a.cos() + v * b.cos()),
+                r * ((1. - v) * a.sin() + v * b.sin()),
+            )
+        };
+
+        (x * self.aperture, y * self.aperture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Grid of pairs of random numbers in `[0, 1)`, including the borders.
+    ///
+    fn grid() -> impl Iterator<Item = (f32, f32)> {
+        let n = 20;
+        let at = move |i: u32| (i as f32 / n as f32).min(0.9999);
+
+        (0..=n).flat_map(move |i| (0..=n).map(move |j| (at(i), at(j))))
+    }
+
+    #[test]
+    fn disk_test() {
+        let lens = Lens::new(0.5, 3.);
+
+        for (x, y) in grid().map(|s| lens.sample(s)) {
+            assert!(x * x + y * y <= 0.25 + 1e-6);
+        }
+        assert_eq!(lens.sample((0.5, 0.5)), (0., 0.));
+    }
+
+    #[test]
+    fn polygon_test() {
+        for blades in [3, 5, 6] {
+            let lens = Lens::new(2., 3.).with_blades(blades).with_rotation(20.);
+            let vertex = |i: u32| {
+                let angle = lens.rotation + 2. * PI * i as f32 / blades as f32;
+                (2. * angle.cos(), 2. * angle.sin())
+            };
+
+            // Points lie on the inner side of each edge of the polygon.
+            for (x, y) in grid().map(|s| lens.sample(s)) {
+                for i in 0..blades {
+                    let ((ax, ay), (bx, by)) = (vertex(i), vertex(i + 1));
+                    let cross = (bx - ax) * (y - ay) - (by - ay) * (x - ax);
+                    assert!(cross >= -1e-5, "({x}, {y}) is outside of {blades} blades");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blades_test() {
+        assert_eq!(Lens::new(1., 1.).with_blades(2), Lens::new(1., 1.));
+        assert_eq!(Lens::new(1., 1.).with_blades(7).blades, 7);
+    }
+}
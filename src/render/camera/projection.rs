@@ -0,0 +1,283 @@
+//!
+//! [Camera](super::Camera)'s Projection.
+//!
+//! The [Projection] maps each point of the [Sensor](super::Sensor) to a [Ray](crate::render::Ray)
+//! leaving the camera.
+//!
+//! Besides the usual perspective and orthographic projections, panoramic projections map the
+//! sensor to directions all around the camera position, to render environment maps or VR
+//! previews. They ignore the camera [Fov](super::Fov) and [Lens](super::Lens).
+//!
+
+use std::{f32::consts::PI, fmt::Display};
+
+use crate::{maths::Vector, scene::lights::Environment};
+
+use super::Sensor;
+
+///
+/// Camera projection.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Projection {
+    ///
+    /// Pinhole perspective, rays leave from the camera position through the sensor.
+    ///
+    /// The distance from the camera to the sensor is computed from the camera [Fov](super::Fov).
+    ///
+    #[default]
+    Perspective,
+    ///
+    /// Orthographic projection, rays are all parallel to the camera direction, and leave from a
+    /// rectangle centered on the camera position.
+    ///
+    /// `width` is the width of this rectangle in world units, its height following the aspect
+    /// ratio of the sensor. Orthographic cameras have no depth of field.
+    ///
+    Orthographic { width: f32 },
+    ///
+    /// 360° equirectangular projection, following the same layout as
+    /// [Environment](crate::scene::lights::Environment) maps: the horizontal axis is the azimuth,
+    /// with the camera direction at the center, and the vertical axis the polar angle.
+    ///
+    /// The sensor should be twice as wide as high.
+    ///
+    Equirectangular,
+    ///
+    /// Fisheye projection, of `fov` angular degrees, in a circle inscribed in the sensor.
+    /// Pixels outside the circle have no ray.
+    ///
+    Fisheye { mapping: FisheyeMapping, fov: f32 },
+    ///
+    /// Six faces of a cube centered on the camera, each a 90° perspective view, laid out in a
+    /// 3x2 grid: `+X`, `-X`, `+Y` on the first row and `-Y`, `+Z`, `-Z` on the second one.
+    ///
+    /// Faces follow the usual cubemap orientation conventions, in camera space. The sensor
+    /// should be 3 faces wide and 2 faces high.
+    ///
+    Cubemap,
+}
+
+///
+/// Mapping from angles to distances from the center of a [Fisheye](Projection::Fisheye) image.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    /// The distance to the center is proportional to the angle.
+    #[default]
+    Equidistant,
+    /// Each pixel covers the same solid angle.
+    Equisolid,
+}
+
+impl Projection {
+    ///
+    /// Pinhole perspective projection.
+    ///
+    pub const fn perspective() -> Self {
+        Self::Perspective
+    }
+
+    ///
+    /// Orthographic projection covering `width` world units horizontally.
+    ///
+    /// # Panics
+    /// Panics if `width` is not strictly positive.
+    ///
+    pub fn orthographic(width: f32) -> Self {
+        assert!(
+            width > 0.,
+            "Projection::orthographic: width must be strictly positive."
+        );
+
+        Self::Orthographic { width }
+    }
+
+    ///
+    /// 360° equirectangular projection, for a sensor twice as wide as high.
+    ///
+    pub const fn equirectangular() -> Self {
+        Self::Equirectangular
+    }
+
+    ///
+    /// Fisheye projection, with an angle of view of `fov` angular degrees.
+    ///
+    /// # Panics
+    /// Panics if `fov` is not in `(0, 360]`.
+    ///
+    pub fn fisheye(mapping: FisheyeMapping, fov: f32) -> Self {
+        assert!(
+            fov > 0. && fov <= 360.,
+            "Projection::fisheye: fov must be in (0, 360] degrees."
+        );
+
+        Self::Fisheye { mapping, fov }
+    }
+
+    ///
+    /// Cubemap projection, for a sensor 3 faces wide and 2 faces high.
+    ///
+    pub const fn cubemap() -> Self {
+        Self::Cubemap
+    }
+
+    ///
+    /// Return the camera space direction of the ray going through the point `(x, y)` of the
+    /// sensor, expressed in pixels, for panoramic projections. Also return the spread of the
+    /// ray, i.e. the angle covered by a pixel.
+    ///
+    /// Return `None` for non-panoramic projections, and for points outside the fisheye circle.
+    ///
+    pub fn panoramic_dir(&self, x: f32, y: f32, sensor: &Sensor) -> Option<(Vector, f32)> {
+        let (width, height) = (sensor.width() as f32, sensor.height() as f32);
+
+        match *self {
+            Self::Perspective | Self::Orthographic { .. } => None,
+            Self::Equirectangular => {
+                let dir = Environment::uv_to_dir((x / width, y / height));
+
+                Some((dir, 2. * PI / width))
+            }
+            Self::Fisheye { mapping, fov } => {
+                // Render positions account for non-square pixels.
+                let (render_width, render_height) = sensor.render_size();
+                let radius = render_width.min(render_height) / 2.;
+                let (rx, ry) = sensor.subpixel_pos_to_render_pos(x, y)?;
+                let (dx, dy) = (rx / radius, ry / radius);
+                let r = (dx * dx + dy * dy).sqrt();
+                let max = fov.to_radians() / 2.;
+
+                if r > 1. {
+                    return None;
+                }
+
+                let theta = match mapping {
+                    FisheyeMapping::Equidistant => r * max,
+                    FisheyeMapping::Equisolid => 2. * (r * (max / 2.).sin()).asin(),
+                };
+                let phi = dy.atan2(dx);
+                let dir = Vector::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+
+                Some((dir, max / radius))
+            }
+            Self::Cubemap => {
+                let face_size = (width / 3.).min(height / 2.);
+                let (col, row) = ((x / face_size).floor(), (y / face_size).floor());
+                // Position on the face, from -1 to 1, `t` going down.
+                let s = 2. * (x / face_size - col) - 1.;
+                let t = 2. * (y / face_size - row) - 1.;
+
+                let dir = match (col as u32, row as u32) {
+                    (0, 0) => Vector::new(1., -t, -s),
+                    (1, 0) => Vector::new(-1., -t, s),
+                    (2, 0) => Vector::new(s, 1., t),
+                    (0, 1) => Vector::new(s, -1., -t),
+                    (1, 1) => Vector::new(s, -t, 1.),
+                    (2, 1) => Vector::new(-s, -t, -1.),
+                    _ => return None,
+                };
+
+                Some((dir.normalize(), 2. / face_size))
+            }
+        }
+    }
+}
+
+impl Display for Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Perspective => f.write_str("Perspective"),
+            Self::Orthographic { width } => f.write_fmt(format_args!("Orthographic {}", width)),
+            Self::Equirectangular => f.write_str("Equirectangular"),
+            Self::Fisheye { mapping, fov } => {
+                f.write_fmt(format_args!("Fisheye {:?} {}", mapping, fov))
+            }
+            Self::Cubemap => f.write_str("Cubemap"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Projection::orthographic: width must be strictly positive.")]
+    fn orthographic_width_test() {
+        Projection::orthographic(0.);
+    }
+
+    #[test]
+    #[should_panic(expected = "Projection::fisheye: fov must be in (0, 360] degrees.")]
+    fn fisheye_fov_test() {
+        Projection::fisheye(FisheyeMapping::Equisolid, 370.);
+    }
+
+    #[test]
+    fn equirectangular_test() {
+        let sensor = Sensor::new(64, 32);
+        let dir = |x, y| {
+            Projection::equirectangular()
+                .panoramic_dir(x, y, &sensor)
+                .unwrap()
+                .0
+        };
+
+        // The center looks forward, the top and bottom rows up and down, and the sides backward.
+        assert!((dir(32., 16.) - Vector::Z).magn() <= 1e-6);
+        assert!((dir(16., 16.) + Vector::X).magn() <= 1e-6);
+        assert!((dir(48., 16.) - Vector::X).magn() <= 1e-6);
+        assert!((dir(20., 0.) - Vector::Y).magn() <= 1e-6);
+        assert!((dir(20., 32.) + Vector::Y).magn() <= 1e-6);
+        assert!((dir(0., 16.) + Vector::Z).magn() <= 1e-6);
+    }
+
+    #[test]
+    fn fisheye_test() {
+        let sensor = Sensor::new(32, 32);
+        let fisheye = Projection::fisheye(FisheyeMapping::Equidistant, 180.);
+        let dir = |x, y| fisheye.panoramic_dir(x, y, &sensor).map(|(dir, _)| dir);
+
+        // The center looks forward, and the border of the circle sideways.
+        assert!((dir(16., 16.).unwrap() - Vector::Z).magn() <= 1e-6);
+        assert!(dir(31.99, 16.).unwrap().z.abs() <= 1e-3);
+        assert!(dir(0.5, 0.5).is_none());
+    }
+
+    #[test]
+    fn cubemap_test() {
+        let sensor = Sensor::new(48, 32);
+        let dir = |x, y| {
+            Projection::cubemap()
+                .panoramic_dir(x, y, &sensor)
+                .unwrap()
+                .0
+        };
+
+        // Face centers, in the `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z` layout.
+        let faces = [
+            Vector::X,
+            -Vector::X,
+            Vector::Y,
+            -Vector::Y,
+            Vector::Z,
+            -Vector::Z,
+        ];
+        for (i, face) in faces.into_iter().enumerate() {
+            let (col, row) = ((i % 3) as f32, (i / 3) as f32);
+            let center = dir(col * 16. + 8., row * 16. + 8.);
+            assert!((center - face).magn() <= 1e-6, "face {i}: {center:?}");
+        }
+
+        // The top edge of the `+Z` face is the bottom edge of the `+Y` face.
+        assert!((dir(24., 16.) - Vector::new(0., 1., 1.).normalize()).magn() <= 1e-6);
+        assert!((dir(40., 15.99) - Vector::new(0., 1., 1.).normalize()).magn() <= 1e-3);
+    }
+}
@@ -4,6 +4,13 @@
 //! The `Camera`'s `Sensor` is a rectangle that define the amount of pixel the camera will send
 //! [Rays](crate::render::rays::Ray) to.
 //!
+//! A `Sensor` can also be described physically, by the size of its film in millimeters. Its
+//! pixels then aren't necessarily square, and the field of view can be derived from the focal
+//! length of the camera lens (see [Fov::Focal]).
+//!
+//! Render positions are expressed in pixel heights, relative to the center of the sensor, with
+//! the `y` axis pointing upward.
+//!
 
 use std::fmt::Display;
 
@@ -12,7 +19,8 @@ use crate::render::camera::Fov;
 ///
 /// [Camera](super::Camera)'s Sensor.
 ///
-/// The `Sensor` is just defined as two positive integer `width` and `height`.
+/// The `Sensor` is defined as two positive integer `width` and `height`, its pixel aspect ratio,
+/// and the width of its film.
 /// The distance to the `Camera` origin is computed from the `Camera` [Fov](super::Fov).
 ///
 /// See [module documentation](self) or [camera's module documentation](super) for more
@@ -22,11 +30,18 @@ use crate::render::camera::Fov;
 pub struct Sensor {
     width: u32,
     height: u32,
+    /// Width of a pixel divided by its height.
+    pixel_aspect: f32,
+    /// Width of the film, in millimeters.
+    film_width: f32,
 }
 
+/// Width of a full frame 35mm film, in millimeters.
+const FULL_FRAME_WIDTH: f32 = 36.;
+
 impl Sensor {
     ///
-    /// Create a new `Sensor`
+    /// Create a new `Sensor`, with square pixels and a 36mm wide film.
     ///
     /// # Panics:
     /// Panics if `width` or `Height` are not strictly positives integers.
@@ -38,19 +53,136 @@ impl Sensor {
             "Sensor::new: height must be a positive integer."
         );
 
-        Self { width, height }
+        Self {
+            width,
+            height,
+            pixel_aspect: 1.,
+            film_width: FULL_FRAME_WIDTH,
+        }
+    }
+
+    ///
+    /// Create a new `Sensor` of `width` x `height` pixels, covering a film of `film_width` x
+    /// `film_height` millimeters.
+    ///
+    /// The pixel aspect ratio follows from the film and pixel sizes.
+    ///
+    /// # Panics:
+    /// Panics if `width` or `Height` are not strictly positives integers, or if the film size is
+    /// not strictly positive.
+    ///
+    pub fn from_film(width: u32, height: u32, film_width: f32, film_height: f32) -> Self {
+        assert!(
+            film_width > 0. && film_height > 0.,
+            "Sensor::from_film: film size must be strictly positive."
+        );
+
+        let pixel_aspect = (film_width / width as f32) / (film_height / height as f32);
+
+        Self {
+            pixel_aspect,
+            film_width,
+            ..Self::new(width, height)
+        }
+    }
+
+    ///
+    /// Set the pixel aspect ratio, i.e. the width of a pixel divided by its height.
+    ///
+    /// # Panics:
+    /// Panics if `pixel_aspect` is not strictly positive.
+    ///
+    pub fn with_pixel_aspect(mut self, pixel_aspect: f32) -> Self {
+        assert!(
+            pixel_aspect > 0.,
+            "Sensor::with_pixel_aspect: pixel aspect must be strictly positive."
+        );
+
+        self.pixel_aspect = pixel_aspect;
+        self
+    }
+
+    ///
+    /// Set the width of the film, in millimeters. The film height follows from the pixel aspect
+    /// ratio.
+    ///
+    /// # Panics:
+    /// Panics if `film_width` is not strictly positive.
+    ///
+    pub fn with_film_width(mut self, film_width: f32) -> Self {
+        assert!(
+            film_width > 0.,
+            "Sensor::with_film_width: film width must be strictly positive."
+        );
+
+        self.film_width = film_width;
+        self
+    }
+
+    ///
+    /// Return the sensor width, in pixels.
+    ///
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    ///
+    /// Return the sensor height, in pixels.
+    ///
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    ///
+    /// Return the pixel aspect ratio, i.e. the width of a pixel divided by its height.
+    ///
+    pub fn pixel_aspect(&self) -> f32 {
+        self.pixel_aspect
+    }
+
+    ///
+    /// Return the film width and height, in millimeters.
+    ///
+    pub fn film_size(&self) -> (f32, f32) {
+        let (width, height) = self.render_size();
+
+        (self.film_width, self.film_width * height / width)
+    }
+
+    ///
+    /// Return the sensor width and height in render units, i.e. in pixel heights.
+    ///
+    pub fn render_size(&self) -> (f32, f32) {
+        (self.width as f32 * self.pixel_aspect, self.height as f32)
     }
 
     ///
     /// Compute the distance between a `Camera` and its sensor, given the `Camera`'s `Fov`.
     ///
+    /// The distance is expressed in render units, i.e. in pixel heights.
+    ///
     pub fn distance(self, fov: Fov) -> f32 {
+        let (width, height) = self.render_size();
+
         match fov {
-            Fov::Horizontal(angle) => (self.width / 2) as f32 / angle.to_radians().tan(),
-            Fov::Vertical(angle) => (self.height / 2) as f32 / angle.to_radians().tan(),
+            Fov::Horizontal(angle) => width / 2. / (angle.to_radians() / 2.).tan(),
+            Fov::Vertical(angle) => height / 2. / (angle.to_radians() / 2.).tan(),
+            Fov::Focal(focal) => width * focal / self.film_width,
         }
     }
 
+    ///
+    /// Return the horizontal and vertical angles of view, in angular degree, given the `Camera`'s
+    /// `Fov`.
+    ///
+    pub fn angles(self, fov: Fov) -> (f32, f32) {
+        let (width, height) = self.render_size();
+        let dist = self.distance(fov);
+        let angle = |size: f32| 2. * (size / 2.).atan2(dist).to_degrees();
+
+        (angle(width), angle(height))
+    }
+
     ///
     /// Check whether the pixel `(x, y)` is in bounds.
     ///
@@ -59,22 +191,29 @@ impl Sensor {
     }
 
     ///
-    /// Convert pixel to render position.
+    /// Convert pixel to render position, at the center of the pixel.
     ///
     /// This function remove half the screen width from the `x` and `y` coordinates, then flip the
     /// `y` coordinate, because this library use NDC space.
     ///
     pub fn pixel_pos_to_render_pos(&self, x: u32, y: u32) -> Option<(f32, f32)> {
-        if !self.has_pixel(x, y) {
+        self.subpixel_pos_to_render_pos(x as f32 + 0.5, y as f32 + 0.5)
+    }
+
+    ///
+    /// Convert a sub-pixel position to render position.
+    ///
+    /// Works like [pixel_pos_to_render_pos](Self::pixel_pos_to_render_pos), but `(x, y)` can be
+    /// anywhere inside a pixel: `(x.5, y.5)` is the center of the pixel `(x, y)`.
+    ///
+    pub fn subpixel_pos_to_render_pos(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        if !(0. ..self.width as f32).contains(&x) || !(0. ..self.height as f32).contains(&y) {
             None
         } else {
-            let half_w = (self.width / 2) as i32;
-            let half_h = (self.height / 2) as i32;
+            let half_w = self.width as f32 / 2.;
+            let half_h = self.height as f32 / 2.;
 
-            let x = ((x as i32) - half_w) as f32;
-            let y = (-((y as i32) - half_h)) as f32;
-
-            Some((x, y))
+            Some(((x - half_w) * self.pixel_aspect, -(y - half_h)))
         }
     }
 }
@@ -88,8 +227,102 @@ impl Default for Sensor {
 impl Display for Sensor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!(
-            "{{ width: {}, height: {} }}",
-            self.width, self.height
+            "{{ width: {}, height: {}, pixel aspect: {}, film width: {}mm }}",
+            self.width, self.height, self.pixel_aspect, self.film_width
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subpixel_pos_test() {
+        let sensor = Sensor::new(4, 2);
+
+        assert_eq!(sensor.subpixel_pos_to_render_pos(0., 0.), Some((-2., 1.)));
+        assert_eq!(sensor.subpixel_pos_to_render_pos(2., 1.), Some((0., 0.)));
+        assert_eq!(
+            sensor.subpixel_pos_to_render_pos(3.5, 1.5),
+            Some((1.5, -0.5))
+        );
+    }
+
+    #[test]
+    fn subpixel_pos_bounds_test() {
+        let sensor = Sensor::new(4, 2);
+
+        assert!(sensor.subpixel_pos_to_render_pos(-0.1, 1.).is_none());
+        assert!(sensor.subpixel_pos_to_render_pos(4., 1.).is_none());
+        assert!(sensor.subpixel_pos_to_render_pos(1., 2.).is_none());
+        assert!(sensor.subpixel_pos_to_render_pos(3.99, 1.99).is_some());
+    }
+
+    #[test]
+    fn subpixel_pos_aspect_test() {
+        let sensor = Sensor::new(4, 2).with_pixel_aspect(2.);
+
+        assert_eq!(sensor.subpixel_pos_to_render_pos(0., 0.), Some((-4., 1.)));
+    }
+
+    #[test]
+    fn odd_size_test() {
+        let sensor = Sensor::new(5, 3);
+
+        // Pixel centers are symmetric around the center of the sensor.
+        assert_eq!(sensor.pixel_pos_to_render_pos(0, 0), Some((-2., 1.)));
+        assert_eq!(sensor.pixel_pos_to_render_pos(4, 2), Some((2., -1.)));
+        assert_eq!(sensor.pixel_pos_to_render_pos(2, 1), Some((0., 0.)));
+    }
+
+    #[test]
+    fn distance_test() {
+        let sensor = Sensor::new(512, 256);
+
+        // The angle of view spans the whole sensor, so the distance uses half the angle.
+        assert!((sensor.distance(Fov::horiz(90.)) - 256.).abs() <= 1e-3);
+        assert!((sensor.distance(Fov::vert(90.)) - 128.).abs() <= 1e-3);
+
+        let (horizontal, vertical) = sensor.angles(Fov::horiz(70.));
+        assert!((horizontal - 70.).abs() <= 1e-3);
+        assert!(vertical < horizontal);
+    }
+
+    #[test]
+    fn focal_test() {
+        // An 18mm lens on a 36mm film sees 90°, like a 36mm film at its focal distance.
+        let sensor = Sensor::new(600, 400);
+        let (horizontal, vertical) = sensor.angles(Fov::focal(18.));
+        assert!((horizontal - 90.).abs() <= 1e-3);
+
+        // Converting the angles back gives the same distance.
+        let dist = sensor.distance(Fov::focal(18.));
+        assert!((sensor.distance(Fov::horiz(horizontal)) - dist).abs() <= 1e-2);
+        assert!((sensor.distance(Fov::vert(vertical)) - dist).abs() <= 1e-2);
+
+        // A wider film sees more with the same lens.
+        let wide = sensor.with_film_width(72.);
+        assert!(wide.angles(Fov::focal(18.)).0 > horizontal);
+    }
+
+    #[test]
+    fn pixel_aspect_test() {
+        // Anamorphic pixels twice as wide as high.
+        let sensor = Sensor::new(4, 2).with_pixel_aspect(2.);
+
+        assert_eq!(sensor.render_size(), (8., 2.));
+        assert_eq!(sensor.film_size(), (36., 9.));
+        assert!((sensor.distance(Fov::horiz(90.)) - 4.).abs() <= 1e-5);
+
+        // The pixel aspect follows from the film size.
+        let sensor = Sensor::from_film(4, 2, 36., 9.);
+        assert!((sensor.pixel_aspect() - 2.).abs() <= 1e-6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sensor::with_film_width: film width must be strictly positive.")]
+    fn film_width_test() {
+        Sensor::new(4, 2).with_film_width(0.);
+    }
+}
@@ -0,0 +1,209 @@
+//!
+//! Film buffer.
+//!
+//! The [Film] accumulates samples shot through the camera [Sensor](super::Sensor). Each sample is
+//! splatted on all the pixels in range of the reconstruction [Filter], weighted by it. The final
+//! pixel colors are the weighted averages of the samples they received.
+//!
+
+use crate::colors::Color;
+
+use super::Filter;
+
+///
+/// Film buffer, accumulating weighted samples.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone)]
+pub struct Film {
+    width: u32,
+    height: u32,
+    filter: Filter,
+    pixels: Vec<Pixel>,
+}
+
+///
+/// Samples accumulated by a pixel of a [Film].
+///
+/// Filters with negative lobes may give a null or negative sum of weights, for instance when the
+/// only samples of a pixel fall in these lobes. The samples weighted by the absolute value of their
+/// weights are accumulated separately, as a fallback for these pixels.
+///
+#[derive(Debug, Clone, Copy)]
+struct Pixel {
+    /// Weighted sum of the samples colors.
+    sum: Color,
+    /// Sum of the samples weights.
+    total: f32,
+    /// Sum of the samples colors, weighted by the absolute value of their weights.
+    abs_sum: Color,
+    /// Sum of the absolute values of the samples weights.
+    abs_total: f32,
+}
+
+impl Pixel {
+    const EMPTY: Self = Self {
+        sum: Color::TRANSPARENT,
+        total: 0.,
+        abs_sum: Color::TRANSPARENT,
+        abs_total: 0.,
+    };
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32, filter: Filter) -> Self {
+        Self {
+            width,
+            height,
+            filter,
+            pixels: vec![Pixel::EMPTY; (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn filter(&self) -> Filter {
+        self.filter
+    }
+
+    ///
+    /// Add a sample, at the sub-pixel position `(x, y)`.
+    ///
+    /// Pixel centers lie at half-integer positions, so a sample at `(x.5, y.5)` falls on the center
+    /// of the pixel `(x, y)`.
+    ///
+    pub fn add_sample(&mut self, x: f32, y: f32, color: Color) {
+        let radius = self.filter.radius();
+
+        let x0 = (x - 0.5 - radius).ceil().max(0.) as u32;
+        let y0 = (y - 0.5 - radius).ceil().max(0.) as u32;
+        let x1 = ((x - 0.5 + radius).floor() as i64).min(self.width as i64 - 1);
+        let y1 = ((y - 0.5 + radius).floor() as i64).min(self.height as i64 - 1);
+
+        for py in y0 as i64..=y1 {
+            for px in x0 as i64..=x1 {
+                let weight = self.filter.weight(px as f32 + 0.5 - x, py as f32 + 0.5 - y);
+
+                if weight != 0. {
+                    let pixel = &mut self.pixels[(py * self.width as i64 + px) as usize];
+                    pixel.sum += color * weight;
+                    pixel.total += weight;
+                    pixel.abs_sum += color * weight.abs();
+                    pixel.abs_total += weight.abs();
+                }
+            }
+        }
+    }
+
+    ///
+    /// Return the reconstructed color of the pixel `(x, y)`.
+    ///
+    /// Pixels whose weights don't sum to a positive value fall back on the average of their samples
+    /// weighted by the absolute value of their weights. Pixels which didn't receive any sample are
+    /// transparent.
+    ///
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let pixel = self.pixels[(y * self.width + x) as usize];
+
+        if pixel.total > 0. {
+            pixel.sum / pixel.total
+        } else if pixel.abs_total > 0. {
+            pixel.abs_sum / pixel.abs_total
+        } else {
+            Color::TRANSPARENT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_average_test() {
+        let mut film = Film::new(2, 2, Filter::default());
+        film.add_sample(0.25, 0.25, Color::new(1., 0., 0., 1.));
+        film.add_sample(0.75, 0.75, Color::new(0., 0., 1., 1.));
+        film.add_sample(1.5, 0.5, Color::new(0., 1., 0., 1.));
+
+        assert_eq!(film.pixel(0, 0), Color::new(0.5, 0., 0.5, 1.));
+        assert_eq!(film.pixel(1, 0), Color::new(0., 1., 0., 1.));
+        assert_eq!(film.pixel(0, 1), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn weighted_average_test() {
+        let mut film = Film::new(3, 1, Filter::tent(1.));
+        film.add_sample(1.5, 0.5, Color::new(1., 1., 1., 1.));
+        film.add_sample(1., 0.5, Color::new(0., 0., 0., 1.));
+
+        // Weights are 1 and 0.5 for the center pixel, 0 and 0.5 for the left one, and 0 for the
+        // right one.
+        let center = film.pixel(1, 0);
+        assert!((center.r - 2. / 3.).abs() <= 1e-6 && center.a == 1.);
+        assert_eq!(film.pixel(0, 0), Color::new(0., 0., 0., 1.));
+        assert_eq!(film.pixel(2, 0), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn normalization_test() {
+        let grey = Color::new(0.25, 0.5, 0.75, 1.);
+        let filters = [
+            Filter::default(),
+            Filter::tent(1.5),
+            Filter::gaussian(1.5),
+            Filter::mitchell(2.),
+            Filter::lanczos(3.),
+        ];
+
+        for filter in filters {
+            let mut film = Film::new(4, 4, filter);
+            for j in 0..16 {
+                for i in 0..16 {
+                    film.add_sample(i as f32 / 4. + 0.1, j as f32 / 4. + 0.1, grey);
+                }
+            }
+
+            // A flat image stays flat, whatever the filter.
+            for (x, y) in (0..4).flat_map(|y| (0..4).map(move |x| (x, y))) {
+                let pixel = film.pixel(x, y);
+                assert!((pixel.r - grey.r).abs() <= 1e-4);
+                assert!((pixel.g - grey.g).abs() <= 1e-4);
+                assert!((pixel.b - grey.b).abs() <= 1e-4);
+                assert!((pixel.a - grey.a).abs() <= 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn negative_weights_test() {
+        let filter = Filter::mitchell(2.);
+        let white = Color::new(1., 1., 1., 1.);
+        let blue = Color::new(0., 0., 1., 1.);
+
+        // The sample only falls in the negative lobe of the last pixel.
+        let mut film = Film::new(3, 1, filter);
+        film.add_sample(1., 0.5, white);
+
+        assert!(filter.weight(1.5, 0.) < 0.);
+        assert_eq!(film.pixel(2, 0), white);
+
+        // Two samples in the negative lobe outweigh one in the positive part.
+        film.add_sample(1., 0.5, white);
+        film.add_sample(3.5, 0.5, blue);
+
+        let (neg, pos) = (filter.weight(1.5, 0.), filter.weight(1., 0.));
+        assert!(2. * neg + pos <= 0.);
+
+        let pixel = film.pixel(2, 0);
+        assert!((pixel.r - 2. * neg / (2. * neg - pos)).abs() <= 1e-5);
+        assert!((pixel.b - 1.).abs() <= 1e-5 && (pixel.a - 1.).abs() <= 1e-5);
+    }
+}
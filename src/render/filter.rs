@@ -0,0 +1,224 @@
+//!
+//! Pixel reconstruction filters.
+//!
+//! A pixel is not a little square: the color of a pixel is a weighted average of the samples
+//! taken around its center, the weights being given by a [Filter]. Wider filters let samples
+//! contribute to several pixels, trading sharpness for smoother edges and less aliasing.
+//!
+//! All filters are separable, that is the weight of a sample at `(dx, dy)` from a pixel center is
+//! `f(dx) * f(dy)`.
+//!
+
+use std::f32::consts::PI;
+
+///
+/// Pixel reconstruction filter.
+///
+/// Each filter has a radius, expressed in pixels, beyond which samples are ignored. The
+/// constructors check that it is positive.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /// Constant weight, i.e. samples are simply averaged.
+    Box { radius: f32 },
+    /// Weight decreasing linearly from the pixel center.
+    Tent { radius: f32 },
+    /// Gaussian bell of falloff `alpha`, shifted so that it reaches 0 at the radius.
+    Gaussian { radius: f32, alpha: f32 },
+    /// Mitchell-Netravali cubic filter, parametrized by `b` and `c`.
+    Mitchell { radius: f32, b: f32, c: f32 },
+    /// Sinc filter, windowed by a Lanczos window of `tau` lobes.
+    Lanczos { radius: f32, tau: f32 },
+}
+
+impl Filter {
+    pub fn box_filter(radius: f32) -> Self {
+        assert_radius(radius);
+        Self::Box { radius }
+    }
+
+    pub fn tent(radius: f32) -> Self {
+        assert_radius(radius);
+        Self::Tent { radius }
+    }
+
+    pub fn gaussian(radius: f32) -> Self {
+        assert_radius(radius);
+        Self::Gaussian { radius, alpha: 2. }
+    }
+
+    ///
+    /// Mitchell-Netravali filter, with the `b = c = 1/3` parameters recommended by its authors.
+    ///
+    pub fn mitchell(radius: f32) -> Self {
+        assert_radius(radius);
+        Self::Mitchell {
+            radius,
+            b: 1. / 3.,
+            c: 1. / 3.,
+        }
+    }
+
+    pub fn lanczos(radius: f32) -> Self {
+        assert_radius(radius);
+        Self::Lanczos { radius, tau: 3. }
+    }
+
+    ///
+    /// Return the filter radius, in pixels.
+    ///
+    pub fn radius(&self) -> f32 {
+        match *self {
+            Self::Box { radius }
+            | Self::Tent { radius }
+            | Self::Gaussian { radius, .. }
+            | Self::Mitchell { radius, .. }
+            | Self::Lanczos { radius, .. } => radius,
+        }
+    }
+
+    ///
+    /// Compute the weight of a sample at `(dx, dy)` pixels from a pixel center.
+    ///
+    /// Some filters (Mitchell-Netravali and Lanczos) have negative lobes, which sharpen edges.
+    ///
+    pub fn weight(&self, dx: f32, dy: f32) -> f32 {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+
+    fn weight_1d(&self, x: f32) -> f32 {
+        let x = x.abs();
+
+        if x > self.radius() {
+            return 0.;
+        }
+
+        match *self {
+            Self::Box { .. } => 1.,
+            Self::Tent { radius } => radius - x,
+            Self::Gaussian { radius, alpha } => {
+                ((-alpha * x * x).exp() - (-alpha * radius * radius).exp()).max(0.)
+            }
+            Self::Mitchell { radius, b, c } => {
+                let x = 2. * x / radius;
+
+                if x > 1. {
+                    ((-b - 6. * c) * x.powi(3)
+                        + (6. * b + 30. * c) * x.powi(2)
+                        + (-12. * b - 48. * c) * x
+                        + (8. * b + 24. * c))
+                        / 6.
+                } else {
+                    ((12. - 9. * b - 6. * c) * x.powi(3)
+                        + (-18. + 12. * b + 6. * c) * x.powi(2)
+                        + (6. - 2. * b))
+                        / 6.
+                }
+            }
+            Self::Lanczos { tau, .. } => sinc(x) * sinc(x / tau),
+        }
+    }
+}
+
+fn assert_radius(radius: f32) {
+    assert!(radius > 0., "filter radius must be positive (got {radius})");
+}
+
+///
+/// Normalized sinc function, `sin(πx) / πx`.
+///
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-5 {
+        1.
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+impl Default for Filter {
+    ///
+    /// Default filter is a box covering exactly one pixel, so that each pixel is the plain average
+    /// of its own samples.
+    ///
+    fn default() -> Self {
+        Self::box_filter(0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Integrate a filter over its support, with the midpoint rule.
+    ///
+    fn integral(filter: Filter) -> f32 {
+        let r = filter.radius();
+        let n = 200;
+        let step = 2. * r / n as f32;
+
+        (0..n)
+            .flat_map(|j| (0..n).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let x = -r + (i as f32 + 0.5) * step;
+                let y = -r + (j as f32 + 0.5) * step;
+                filter.weight(x, y) * step * step
+            })
+            .sum()
+    }
+
+    #[test]
+    fn box_test() {
+        let filter = Filter::default();
+
+        assert_eq!(filter.radius(), 0.5);
+        assert_eq!(filter.weight(0., 0.), 1.);
+        assert_eq!(filter.weight(0.5, -0.5), 1.);
+        assert_eq!(filter.weight(0.51, 0.), 0.);
+        assert_eq!(filter.weight(0., -0.6), 0.);
+    }
+
+    #[test]
+    fn weight_test() {
+        let filters = [
+            Filter::tent(1.5),
+            Filter::gaussian(1.5),
+            Filter::mitchell(2.),
+            Filter::lanczos(3.),
+        ];
+
+        for filter in filters {
+            let r = filter.radius();
+            let center = filter.weight(0., 0.);
+
+            // Peak at the center, symmetric, separable, and null beyond the radius.
+            assert!(center > 0.);
+            assert!(filter.weight(0.3, 0.2) <= center);
+            assert!((filter.weight(0.7, -0.4) - filter.weight(-0.7, 0.4)).abs() <= 1e-6);
+            assert!(
+                (filter.weight(0.7, 0.4) * center
+                    - filter.weight(0.7, 0.) * filter.weight(0., 0.4))
+                .abs()
+                    <= 1e-5
+            );
+            assert_eq!(filter.weight(r + 0.01, 0.), 0.);
+            assert!(filter.weight(r - 0.01, 0.).abs() <= 0.05 * center);
+
+            // Samples are averaged by the sum of their weights, so only a positive integral
+            // matters.
+            assert!(integral(filter) > 0.);
+        }
+    }
+
+    #[test]
+    fn negative_lobes_test() {
+        assert!(Filter::mitchell(2.).weight(1.5, 0.) < 0.);
+        assert!(Filter::lanczos(3.).weight(1.5, 0.) < 0.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn radius_test() {
+        Filter::gaussian(0.);
+    }
+}
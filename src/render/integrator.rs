@@ -0,0 +1,45 @@
+//!
+//! Rendering integrators.
+//!
+//! An [Integrator] computes the light coming back along a camera [Ray], i.e. the color of a
+//! sample. It owns the whole light transport algorithm, so that the [Renderer](super::Renderer)
+//! only has to shoot rays through the camera [Sensor](super::Sensor) and reconstruct pixels.
+//!
+
+pub mod ambient_occlusion;
+pub mod normals;
+pub mod path_tracer;
+pub mod shading;
+pub mod whitted;
+
+pub use ambient_occlusion::AmbientOcclusion;
+pub use normals::Normals;
+pub use path_tracer::PathTracer;
+pub use shading::Shading;
+pub use whitted::Whitted;
+
+use nannou::rand::rngs::SmallRng;
+
+use crate::{colors::Color, scene::Scene};
+
+use super::{Background, Ray};
+
+///
+/// Primary trait for integrators.
+///
+pub trait Integrator: Send + Sync {
+    ///
+    /// Compute the linear color coming back along a camera `ray`.
+    ///
+    /// `background` gives the light coming from rays escaping the scene. Random decisions draw
+    /// from `rng`, which the [Renderer](super::Renderer) seeds for each pixel so that renders are
+    /// reproducible.
+    ///
+    fn radiance(
+        &self,
+        ray: Ray,
+        scene: &Scene,
+        background: &Background,
+        rng: &mut SmallRng,
+    ) -> Color;
+}
@@ -0,0 +1,134 @@
+//!
+//! Ambient occlusion integrator.
+//!
+
+use nannou::rand::{rngs::SmallRng, Rng};
+
+use crate::{
+    colors::Color,
+    maths::sampling,
+    render::{Background, Ray},
+    scene::Scene,
+};
+
+use super::Integrator;
+
+/// Offset applied to occlusion rays origins, to avoid hitting the surface they leave.
+const EPSILON: f32 = 1e-4;
+
+///
+/// Ambient occlusion integrator.
+///
+/// At the closest hit, cosine-weighted rays are shot in the hemisphere around the normal. The
+/// returned gray level is the fraction of those rays which don't hit any object closer than
+/// `distance`, so that creases and contact areas appear darker.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientOcclusion {
+    /// Number of occlusion rays per hit.
+    samples: u32,
+    /// Distance beyond which objects don't occlude.
+    distance: f32,
+}
+
+impl AmbientOcclusion {
+    pub fn new(samples: u32, distance: f32) -> Self {
+        Self { samples, distance }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn radiance(
+        &self,
+        ray: Ray,
+        scene: &Scene,
+        background: &Background,
+        rng: &mut SmallRng,
+    ) -> Color {
+        let Some((inter, _)) = scene.intersect(ray) else {
+            return background.color(ray.dir) + scene.emitted(ray.dir)
+        };
+
+        let inter = inter.face_forward(ray.dir);
+        let normal = inter.normal;
+        let orig = inter.pos + inter.geometric_normal * EPSILON;
+
+        let visible = (0..self.samples)
+            .map(|_| sampling::cosine_hemisphere(normal, (rng.gen(), rng.gen())))
+            .filter(|dir| !scene.occluded(Ray::new(orig, *dir), self.distance))
+            .count();
+        let v = visible as f32 / self.samples.max(1) as f32;
+
+        Color::from_rgb(v, v, v)
+    }
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        Self::new(16, f32::INFINITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nannou::rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        maths::{Point, Vector},
+        scene::{
+            objects::{Plan, Sphere},
+            Object,
+        },
+    };
+
+    fn occlusion(ao: AmbientOcclusion, objects: Vec<Box<dyn Object>>, ray: Ray) -> f32 {
+        let scene = Scene::new(objects, vec![], 0.);
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        ao.radiance(ray, &scene, &Background::solid(Color::BLACK), &mut rng)
+            .r
+    }
+
+    #[test]
+    fn open_test() {
+        let floor = Plan::new(Point::new(0., -1., 0.), Vector::Y);
+        let ray = Ray::new(Point::ORIGIN, Vector::new(0., -1., 1.).normalize());
+
+        assert_eq!(
+            occlusion(AmbientOcclusion::default(), vec![Box::new(floor)], ray),
+            1.
+        );
+    }
+
+    #[test]
+    fn closed_test() {
+        // Seen from inside, a sphere occludes the whole hemisphere.
+        let sphere = || Box::new(Sphere::new(Point::ORIGIN, 3.)) as Box<dyn Object>;
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+
+        assert_eq!(
+            occlusion(AmbientOcclusion::default(), vec![sphere()], ray),
+            0.
+        );
+
+        // Unless the occlusion distance is shorter than the sphere.
+        let ao = AmbientOcclusion::new(16, 0.1);
+        assert_eq!(occlusion(ao, vec![sphere()], ray), 1.);
+    }
+
+    #[test]
+    fn partial_test() {
+        // A wall standing on the floor hides part of the sky.
+        let floor = Plan::new(Point::new(0., -1., 0.), Vector::Y);
+        let wall = Plan::new(Point::new(0., 0., 5.), -Vector::Z);
+        let ray = Ray::new(Point::ORIGIN, Vector::new(0., -1., 4.5).normalize());
+        let v = occlusion(
+            AmbientOcclusion::new(256, f32::INFINITY),
+            vec![Box::new(floor), Box::new(wall)],
+            ray,
+        );
+
+        assert!(v > 0.3 && v < 0.7);
+    }
+}
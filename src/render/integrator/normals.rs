@@ -0,0 +1,81 @@
+//!
+//! Normals debug integrator.
+//!
+
+use nannou::rand::rngs::SmallRng;
+
+use crate::{
+    colors::Color,
+    render::{Background, Ray},
+    scene::Scene,
+};
+
+use super::Integrator;
+
+///
+/// Debug integrator, displaying the surface normals.
+///
+/// Each component of the unit normal is mapped from `[-1, 1]` to `[0, 1]`, so that a normal
+/// pointing toward `+X` is red, `+Y` green and `+Z` blue.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Normals;
+
+impl Integrator for Normals {
+    fn radiance(
+        &self,
+        ray: Ray,
+        scene: &Scene,
+        background: &Background,
+        _rng: &mut SmallRng,
+    ) -> Color {
+        scene
+            .intersect(ray)
+            .map(|(inter, _)| {
+                let n = inter.normal;
+
+                Color::from_rgb((n.x + 1.) / 2., (n.y + 1.) / 2., (n.z + 1.) / 2.)
+            })
+            .unwrap_or_else(|| background.color(ray.dir) + scene.emitted(ray.dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nannou::rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        maths::{Point, Vector},
+        scene::{objects::Sphere, Object},
+    };
+
+    #[test]
+    fn normal_colors_test() {
+        let sphere = Sphere::new(Point::new(0., 0., 5.), 1.);
+        let scene = Scene::new(vec![Box::new(sphere) as Box<dyn Object>], vec![], 0.);
+        let background = Background::solid(Color::BLACK);
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        // The front of the sphere faces -Z.
+        let color = Normals.radiance(
+            Ray::new(Point::ORIGIN, Vector::Z),
+            &scene,
+            &background,
+            &mut rng,
+        );
+        assert!((color.r - 0.5).abs() <= 1e-5 && (color.g - 0.5).abs() <= 1e-5);
+        assert!(color.b.abs() <= 1e-5);
+
+        // Its top faces +Y.
+        let ray = Ray::new(Point::new(0., 5., 5.), -Vector::Y);
+        let color = Normals.radiance(ray, &scene, &background, &mut rng);
+        assert!((color.r - 0.5).abs() <= 1e-5 && (color.g - 1.).abs() <= 1e-5);
+
+        let ray = Ray::new(Point::ORIGIN, -Vector::Z);
+        assert_eq!(
+            Normals.radiance(ray, &scene, &background, &mut rng),
+            Color::BLACK
+        );
+    }
+}
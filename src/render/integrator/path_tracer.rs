@@ -0,0 +1,222 @@
+//!
+//! Unidirectional Monte Carlo path tracer.
+//!
+//! Where the [Shading](super::Shading) shaders only account for light coming straight from the
+//! light sources, a path tracer estimates all the light reaching a point, including light bounced
+//! off other objects (global illumination).
+//!
+//! Starting from the camera, a path is built by randomly bouncing off surfaces. At each bounce:
+//! - Light emitted by the surface [Material](crate::scene::Material) is collected.
+//! - Each [Light](crate::scene::Light) is sampled, and its contribution added if it isn't
+//!   shadowed (next-event estimation).
+//! - The next direction is drawn following the [Material](crate::scene::Material) BRDF
+//!   (importance sampling).
+//!
+//! Paths are terminated by Russian roulette: after a few bounces, a path is randomly stopped with
+//! a probability depending on how much light it can still carry, and surviving paths are
+//! reweighted to keep the estimate unbiased.
+//!
+
+use nannou::rand::{rngs::SmallRng, Rng};
+
+use crate::{
+    colors::Color,
+    maths::{sampling, Point},
+    render::{Background, Ray},
+    scene::Scene,
+};
+
+use super::Integrator;
+
+/// Offset applied to bounced rays origins, to avoid hitting the surface they leave.
+const EPSILON: f32 = 1e-4;
+
+///
+/// Path tracer settings.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathTracer {
+    /// Maximum number of bounces of a path.
+    max_depth: u32,
+    /// Number of bounces before Russian roulette starts.
+    rr_depth: u32,
+}
+
+impl PathTracer {
+    pub fn new(max_depth: u32, rr_depth: u32) -> Self {
+        Self {
+            max_depth,
+            rr_depth,
+        }
+    }
+
+    ///
+    /// Estimate the light coming back along a camera [Ray].
+    ///
+    /// `background` gives the light coming from rays escaping the scene, and random decisions
+    /// draw from `rng`.
+    ///
+    pub fn radiance(
+        &self,
+        ray: Ray,
+        scene: &Scene,
+        background: &Background,
+        rng: &mut impl Rng,
+    ) -> Color {
+        let mut ray = ray;
+        let mut radiance = Color::BLACK;
+        let mut throughput = Color::WHITE;
+        // Origin of the last bounce, and density of the material sampling which drew it.
+        let mut bounce: Option<(Point, f32)> = None;
+
+        for depth in 0..=self.max_depth {
+            let Some((inter, obj)) = scene.intersect(ray) else {
+                radiance +=
+                    throughput * (background.color(ray.dir) + self.escaped(ray, scene, bounce));
+                break
+            };
+
+            let material = obj.material().surface(&inter);
+            let inter = inter.face_forward(ray.dir);
+            let normal = inter.normal;
+            let orig = inter.pos + inter.geometric_normal * EPSILON;
+
+            radiance += throughput * material.emission;
+
+            let wo = -ray.dir;
+            radiance += throughput * scene.direct_light(orig, normal, wo, &material, true, rng);
+
+            // The BRDF is importance sampled, the weight being brdf * cos / pdf.
+            let Some((dir, weight)) = material.sample(normal, wo, (rng.gen(), rng.gen())) else {
+                break
+            };
+            throughput = throughput * weight;
+            bounce = Some((orig, material.pdf(normal, wo, dir)));
+
+            if depth >= self.rr_depth {
+                let survival = throughput.r.max(throughput.g).max(throughput.b).min(0.95);
+
+                if rng.gen::<f32>() >= survival {
+                    break;
+                }
+                throughput = throughput / survival;
+            }
+
+            ray = Ray::new(orig, dir);
+        }
+
+        Color { a: 1., ..radiance }
+    }
+
+    ///
+    /// Compute the light emitted by the lights at infinity toward a [Ray] escaping the scene.
+    ///
+    /// Rays which bounced off a surface could also have reached those lights through next-event
+    /// estimation, so their contribution is weighted by multiple importance sampling.
+    ///
+    fn escaped(&self, ray: Ray, scene: &Scene, bounce: Option<(Point, f32)>) -> Color {
+        let Some((pos, pdf)) = bounce else {
+            return scene.emitted(ray.dir)
+        };
+
+        scene.lights.iter().fold(Color::BLACK, |acc, light| {
+            let light_pdf = light.pdf(pos, ray.dir) * light.samples() as f32;
+
+            acc + light.emitted(ray.dir) * sampling::power_heuristic(pdf, light_pdf)
+        })
+    }
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        Self::new(8, 3)
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(
+        &self,
+        ray: Ray,
+        scene: &Scene,
+        background: &Background,
+        rng: &mut SmallRng,
+    ) -> Color {
+        self.radiance(ray, scene, background, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nannou::rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        maths::Vector,
+        scene::{objects::Sphere, Material, Object},
+    };
+
+    fn sphere(material: Material) -> Scene {
+        let sphere = Sphere::new(Point::new(0., 0., 5.), 1.).with_material(material);
+
+        Scene::new(vec![Box::new(sphere) as Box<dyn Object>], vec![], 0.)
+    }
+
+    ///
+    /// Average `n` paths shot along `ray`.
+    ///
+    fn estimate(scene: &Scene, background: &Background, ray: Ray, n: u32) -> Color {
+        let tracer = PathTracer::default();
+        let mut rng = SmallRng::seed_from_u64(7);
+
+        (0..n).fold(Color::TRANSPARENT, |acc, _| {
+            acc + tracer.radiance(ray, scene, background, &mut rng)
+        }) / n as f32
+    }
+
+    #[test]
+    fn emissive_test() {
+        let scene = sphere(Material::emissive(Color::new(2., 1., 0.5, 1.)));
+        let background = Background::solid(Color::BLACK);
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+
+        assert_eq!(
+            estimate(&scene, &background, ray, 16),
+            Color::new(2., 1., 0.5, 1.)
+        );
+
+        // Rays missing the sphere see the background.
+        let ray = Ray::new(Point::ORIGIN, -Vector::Z);
+        assert_eq!(estimate(&scene, &background, ray, 16), Color::BLACK);
+    }
+
+    #[test]
+    fn furnace_test() {
+        // A convex object lit by a uniform white background reflects its albedo.
+        let grey = Color::new(0.5, 0.5, 0.5, 1.);
+        let scene = sphere(Material::diffuse(grey));
+        let background = Background::solid(Color::WHITE);
+
+        for dir in [Vector::Z, Vector::new(0.15, 0.1, 1.).normalize()] {
+            let color = estimate(&scene, &background, Ray::new(Point::ORIGIN, dir), 4096);
+
+            assert!((color.r - 0.5).abs() <= 1e-4);
+            assert!((color.r - color.g).abs() <= 1e-4 && (color.r - color.b).abs() <= 1e-4);
+            assert!((color.a - 1.).abs() <= 1e-4);
+        }
+    }
+
+    #[test]
+    fn seeded_test() {
+        let scene = sphere(Material::diffuse(Color::WHITE));
+        let background = Background::gradient(Color::BLACK, Color::WHITE);
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+        let tracer = PathTracer::default();
+
+        let a = tracer.radiance(ray, &scene, &background, &mut SmallRng::seed_from_u64(3));
+        let b = tracer.radiance(ray, &scene, &background, &mut SmallRng::seed_from_u64(3));
+
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,120 @@
+//!
+//! Shader chain integrator.
+//!
+
+use nannou::rand::rngs::SmallRng;
+
+use crate::{
+    colors::Color,
+    render::{shader, Background, Ray, Shader},
+    scene::Scene,
+};
+
+use super::Integrator;
+
+///
+/// Shader chain integrator.
+///
+/// The color of the closest hit is computed by folding the [Material](crate::scene::Material)
+/// base color, textures included, through a chain of [Shaders](Shader), each one refining the
+/// color computed by the previous ones. No secondary ray is shot, unless a shader does: see
+/// [Whitted](super::Whitted) for mirror reflections and refractions.
+///
+pub struct Shading {
+    shaders: Vec<Box<dyn Shader>>,
+}
+
+impl Shading {
+    pub fn new(shaders: Vec<Box<dyn Shader>>) -> Self {
+        Self { shaders }
+    }
+
+    ///
+    /// Append a [Shader] at the end of the chain.
+    ///
+    pub fn with_shader(mut self, shader: impl Shader + 'static) -> Self {
+        self.shaders.push(Box::new(shader));
+        self
+    }
+}
+
+impl Integrator for Shading {
+    fn radiance(
+        &self,
+        ray: Ray,
+        scene: &Scene,
+        background: &Background,
+        rng: &mut SmallRng,
+    ) -> Color {
+        scene
+            .intersect(ray)
+            .map(|(inter, obj)| {
+                self.shaders
+                    .iter()
+                    .fold(obj.material().surface(&inter).color, |col, s| {
+                        s.compute_color(ray, obj, inter, col, scene, rng)
+                    })
+            })
+            .unwrap_or_else(|| background.color(ray.dir) + scene.emitted(ray.dir))
+    }
+}
+
+impl Default for Shading {
+    fn default() -> Self {
+        Self::new(vec![Box::new(shader::Fast)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nannou::rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        maths::{Point, Vector},
+        scene::{objects::Plan, Material, Object},
+    };
+
+    #[test]
+    fn shaders_fold_test() {
+        let plan = Plan::new(Point::new(0., 0., 4.), -Vector::Z)
+            .with_material(Material::diffuse(Color::new(1., 0.5, 0., 1.)));
+        let scene = Scene::new(vec![Box::new(plan) as Box<dyn Object>], vec![], 0.);
+        let background = Background::solid(Color::BLACK);
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        // The fast shader dims the base color with the incidence angle.
+        let dir = Vector::new(0., 1., 1.).normalize();
+        let color = Shading::default().radiance(
+            Ray::new(Point::ORIGIN, dir),
+            &scene,
+            &background,
+            &mut rng,
+        );
+        let cos = dir.z;
+
+        assert!((color.r - cos).abs() <= 1e-5 && (color.g - 0.5 * cos).abs() <= 1e-5);
+        assert_eq!(color.b, 0.);
+
+        // Without shaders, the base color is returned as is.
+        let color = Shading::new(vec![]).radiance(
+            Ray::new(Point::ORIGIN, dir),
+            &scene,
+            &background,
+            &mut rng,
+        );
+        assert_eq!(color, Color::new(1., 0.5, 0., 1.));
+    }
+
+    #[test]
+    fn miss_test() {
+        let background = Background::solid(Color::new(0.2, 0.3, 0.4, 1.));
+        let mut rng = SmallRng::seed_from_u64(0);
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+
+        assert_eq!(
+            Shading::default().radiance(ray, &Scene::empty(), &background, &mut rng),
+            Color::new(0.2, 0.3, 0.4, 1.)
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//!
+//! Whitted ray tracer.
+//!
+//! Whitted ray tracing follows the perfectly specular paths of light. At each hit:
+//! - The surface is lit by each [Light](crate::scene::Light) which isn't shadowed, plus the
+//!   scene ambient light and the [Material](crate::scene::Material) emission.
+//! - Smooth surfaces reflect a mirror ray, weighted by their Fresnel reflectance.
+//! - Transmissive surfaces refract a ray following Snell's law, weighted by the light which
+//!   isn't reflected. Past the critical angle, all the light is reflected.
+//!
+//! Secondary rays are traced recursively, up to a maximum depth. Unlike the
+//! [PathTracer](super::PathTracer), light bounced off rough surfaces is not accounted for.
+//!
+
+use nannou::rand::rngs::SmallRng;
+
+use crate::{
+    colors::Color,
+    maths::{microfacet, Vector},
+    render::{Background, Ray},
+    scene::Scene,
+};
+
+use super::Integrator;
+
+/// Offset applied to secondary rays origins, to avoid hitting the surface they leave.
+const EPSILON: f32 = 1e-4;
+
+/// Roughness under which surfaces are considered smooth enough to reflect mirror rays.
+const SMOOTH_ROUGHNESS: f32 = 0.1;
+
+///
+/// Whitted ray tracer settings.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Whitted {
+    /// Maximum number of reflections and refractions along a path.
+    max_depth: u32,
+}
+
+impl Whitted {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+
+    ///
+    /// Compute the light coming back along `ray`, which already went through `depth` reflections
+    /// or refractions.
+    ///
+    fn trace(
+        &self,
+        ray: Ray,
+        scene: &Scene,
+        background: &Background,
+        rng: &mut SmallRng,
+        depth: u32,
+    ) -> Color {
+        let Some((inter, obj)) = scene.intersect(ray) else {
+            return background.color(ray.dir) + scene.emitted(ray.dir)
+        };
+
+        let material = obj.material();
+        let surface = material.surface(&inter);
+        let entering = inter.geometric_normal.dot(ray.dir) < 0.;
+        let inter = inter.face_forward(ray.dir);
+        let (normal, wo) = (inter.normal, -ray.dir);
+        let outside = inter.pos + inter.geometric_normal * EPSILON;
+        let inside = inter.pos - inter.geometric_normal * EPSILON;
+
+        // The transmitted light isn't reflected by the surface itself.
+        let opacity = 1. - material.transmission;
+        let local = surface.color * scene.ambiant
+            + scene.direct_light(outside, normal, wo, &surface, false, rng);
+        let mut radiance = surface.emission + local * opacity;
+
+        let specular = surface.roughness <= SMOOTH_ROUGHNESS || material.transmission > 0.;
+        if depth >= self.max_depth || !specular {
+            return Color { a: 1., ..radiance };
+        }
+
+        let cos = normal.dot(wo);
+        let mut reflectance = surface.fresnel(cos) * opacity;
+
+        if material.transmission > 0. {
+            let eta = if entering {
+                material.ior.recip()
+            } else {
+                material.ior
+            };
+            let refracted = refract(wo, normal, eta);
+            let fresnel =
+                refracted.map_or(1., |wt| dielectric_fresnel(material.ior, cos, wt, normal));
+
+            reflectance += Color::WHITE * (fresnel * material.transmission);
+
+            if let Some(wt) = refracted {
+                let transmittance = surface.color * ((1. - fresnel) * material.transmission);
+                let light = self.trace(Ray::new(inside, wt), scene, background, rng, depth + 1);
+                radiance += transmittance * light;
+            }
+        }
+
+        if reflectance.r.max(reflectance.g).max(reflectance.b) > 0. {
+            let wr = normal * (2. * cos) - wo;
+            let light = self.trace(Ray::new(outside, wr), scene, background, rng, depth + 1);
+            radiance += reflectance * light;
+        }
+
+        Color { a: 1., ..radiance }
+    }
+}
+
+impl Default for Whitted {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+impl Integrator for Whitted {
+    fn radiance(
+        &self,
+        ray: Ray,
+        scene: &Scene,
+        background: &Background,
+        rng: &mut SmallRng,
+    ) -> Color {
+        self.trace(ray, scene, background, rng, 0)
+    }
+}
+
+///
+/// Refract the unit direction `wo`, pointing away from a surface of unit normal `n` on the same
+/// side, where `eta` is the ratio of the indices of refraction of the sides of `wo` and of the
+/// refracted direction.
+///
+/// Return `None` past the critical angle, where all the light is reflected.
+///
+fn refract(wo: Vector, n: Vector, eta: f32) -> Option<Vector> {
+    let cos_i = n.dot(wo);
+    let sin2_t = eta * eta * (1. - cos_i * cos_i).max(0.);
+
+    if sin2_t > 1. {
+        return None;
+    }
+    let cos_t = (1. - sin2_t).sqrt();
+
+    Some((-wo * eta + n * (eta * cos_i - cos_t)).normalize())
+}
+
+///
+/// Fresnel reflectance of the boundary of a dielectric of index of refraction `ior`, for light
+/// coming with a cosine `cos_i` to the normal `n`, and refracted toward `wt`.
+///
+/// The Schlick approximation uses the angle on the side of the lower index.
+///
+fn dielectric_fresnel(ior: f32, cos_i: f32, wt: Vector, n: Vector) -> f32 {
+    let f0 = ((ior - 1.) / (ior + 1.)).powi(2);
+    let cos_t = n.dot(-wt);
+
+    microfacet::schlick(f0, cos_i.min(cos_t))
+}
+
+#[cfg(test)]
+mod tests {
+    use nannou::rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        maths::Point,
+        scene::{
+            objects::{Plan, Sphere},
+            Material, Object,
+        },
+    };
+
+    fn radiance(whitted: Whitted, objects: Vec<Box<dyn Object>>, ray: Ray) -> Color {
+        let scene = Scene::new(objects, vec![], 0.);
+        let background = Background::solid(Color::new(0.2, 0.4, 0.8, 1.));
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        whitted.radiance(ray, &scene, &background, &mut rng)
+    }
+
+    #[test]
+    fn refract_test() {
+        let n = Vector::Y;
+        let wo = Vector::new(-1., 1., 0.).normalize();
+
+        // Snell's law: the sines of the angles are in the inverse ratio of the indices.
+        let wt = refract(wo, n, 1. / 1.5).unwrap();
+        let sin_t = wt.cross(-n).magn();
+        assert!((sin_t - 45_f32.to_radians().sin() / 1.5).abs() <= 1e-5);
+        assert!(wt.y < 0. && wt.x > 0.);
+
+        // Straight rays aren't bent, and grazing rays from inside are totally reflected.
+        assert!((refract(n, n, 1. / 1.5).unwrap() + n).magn() <= 1e-6);
+        assert!(refract(wo, n, 1.5).is_none());
+    }
+
+    #[test]
+    fn mirror_test() {
+        // White metal reflects the whole background.
+        let mirror = Plan::new(Point::new(0., 0., 2.), -Vector::Z).with_material(Material::pbr(
+            Color::WHITE,
+            1.,
+            0.,
+        ));
+        let ray = Ray::new(Point::ORIGIN, Vector::new(0.3, 0.2, 1.).normalize());
+
+        let color = radiance(Whitted::default(), vec![Box::new(mirror)], ray);
+        assert!((color.r - 0.2).abs() <= 1e-5 && (color.b - 0.8).abs() <= 1e-5);
+
+        // Lambertian surfaces don't reflect mirror rays.
+        let wall = Plan::new(Point::new(0., 0., 2.), -Vector::Z).with_material(Material::DEFAULT);
+        assert_eq!(
+            radiance(Whitted::default(), vec![Box::new(wall)], ray),
+            Color::from_rgb(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn max_depth_test() {
+        // Two facing emissive mirrors, each halving the light reflected off the other one.
+        let material = Material {
+            emission: Color::WHITE,
+            ..Material::pbr(Color::from_rgb(0.5, 0.5, 0.5), 1., 0.)
+        };
+        let mirrors = || -> Vec<Box<dyn Object>> {
+            vec![
+                Box::new(
+                    Plan::new(Point::new(0., 0., 1.), -Vector::Z).with_material(material.clone()),
+                ),
+                Box::new(
+                    Plan::new(Point::new(0., 0., -1.), Vector::Z).with_material(material.clone()),
+                ),
+            ]
+        };
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+
+        for depth in [0, 1, 4] {
+            let color = radiance(Whitted::new(depth), mirrors(), ray);
+            let expected = 2. * (1. - 0.5_f32.powi(depth as i32 + 1));
+            assert!((color.r - expected).abs() <= 1e-5, "{depth}: {color:?}");
+        }
+    }
+
+    #[test]
+    fn glass_test() {
+        let glass = || -> Vec<Box<dyn Object>> {
+            let material = Material::pbr(Color::WHITE, 0., 0.).with_transmission(1., 1.5);
+            vec![Box::new(
+                Sphere::new(Point::new(0., 0., 4.), 1.).with_material(material),
+            )]
+        };
+
+        // Clear glass neither absorbs nor emits light, so a deep enough trace through it finds the
+        // uniform background back.
+        for dir in [Vector::Z, Vector::new(0.1, 0.2, 1.).normalize()] {
+            let ray = Ray::new(Point::ORIGIN, dir);
+            let color = radiance(Whitted::new(12), glass(), ray);
+
+            assert!(
+                (color.r - 0.2).abs() <= 1e-2 && (color.b - 0.8).abs() <= 1e-2,
+                "{color:?}"
+            );
+        }
+
+        // Without recursion, the glass is black.
+        let ray = Ray::new(Point::ORIGIN, Vector::Z);
+        assert_eq!(
+            radiance(Whitted::new(0), glass(), ray),
+            Color::from_rgb(0., 0., 0.)
+        );
+    }
+}
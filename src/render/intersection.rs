@@ -7,19 +7,191 @@ use crate::maths::{Point, Vector};
 ///
 /// Intersection between a [Ray](super::Ray) and an [Object](crate::scene::Object)
 ///
+/// Besides the hit point and the surface normal, an intersection carries the surface texture
+/// coordinates `uv`, and the tangent frame in which normal maps are expressed: `tangent` follows
+/// the direction of increasing `u`, and `bitangent = normal × tangent`.
+///
+/// `normal` is the shading normal, which normal and bump maps of the object
+/// [Material](crate::scene::Material) may perturb. `geometric_normal` is the true normal of the
+/// surface, which should be used to offset rays leaving it.
+///
+/// The partial derivatives of the hit point with respect to `u` and `v`, and the width of the ray
+/// footprint, give the area of the texture covered by the ray, so that textures can be filtered.
+///
 #[derive(Debug, Copy, Clone)]
 pub struct Intersection {
     pub dist: f32,
     pub pos: Point,
     pub normal: Vector,
+    pub geometric_normal: Vector,
+    pub uv: (f32, f32),
+    pub tangent: Vector,
+    pub bitangent: Vector,
+    /// Partial derivative of the hit point with respect to `u`.
+    pub dpdu: Vector,
+    /// Partial derivative of the hit point with respect to `v`.
+    pub dpdv: Vector,
+    /// Width of the ray footprint on the surface, in world units.
+    pub footprint: f32,
 }
 
 impl Intersection {
+    ///
+    /// Create a new intersection, with null texture coordinates and an arbitrary tangent frame.
+    ///
     pub fn new(dist: f32, pos: Point, normal: Vector) -> Self {
+        let normal = normal.normalize();
+        let (tangent, bitangent) = if normal.is_normalized() {
+            normal.any_orthonormal_pair()
+        } else {
+            (Vector::X, Vector::Y)
+        };
+
         Self {
             dist,
             pos,
-            normal: normal.normalize(),
+            normal,
+            geometric_normal: normal,
+            uv: (0., 0.),
+            tangent,
+            bitangent,
+            dpdu: Vector::ZERO,
+            dpdv: Vector::ZERO,
+            footprint: 0.,
+        }
+    }
+
+    ///
+    /// Set the texture coordinates, and the partial derivatives of the hit point with respect to
+    /// them.
+    ///
+    /// The tangent frame is built from `dpdu`. If it is null or parallel to the normal, e.g. at the
+    /// poles of a sphere, the arbitrary frame is kept.
+    ///
+    pub fn with_uv(mut self, uv: (f32, f32), dpdu: Vector, dpdv: Vector) -> Self {
+        self.uv = uv;
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+
+        let tangent = dpdu - self.normal * self.normal.dot(dpdu);
+        if tangent.magn2() > 1e-12 {
+            self.tangent = tangent.normalize();
+            self.bitangent = self.normal.cross(self.tangent);
+        }
+
+        self
+    }
+
+    ///
+    /// Replace the shading normal, keeping the geometric normal.
+    ///
+    /// The tangent frame is rotated to stay orthonormal with the new normal.
+    ///
+    pub fn with_shading_normal(mut self, normal: Vector) -> Self {
+        let tangent = self.tangent - normal * normal.dot(self.tangent);
+
+        self.normal = normal;
+        if tangent.magn2() > 1e-12 {
+            self.tangent = tangent.normalize();
+        } else {
+            self.tangent = normal.any_orthonormal();
+        }
+        self.bitangent = normal.cross(self.tangent);
+
+        self
+    }
+
+    ///
+    /// Flip the shading and geometric normals if needed, so that the geometric normal faces the
+    /// side a ray of direction `dir` comes from. The tangent frame is flipped along.
+    ///
+    pub fn face_forward(mut self, dir: Vector) -> Self {
+        if self.geometric_normal.dot(dir) > 0. {
+            self.normal = -self.normal;
+            self.geometric_normal = -self.geometric_normal;
+            // Keep `bitangent = normal × tangent`.
+            self.bitangent = -self.bitangent;
+        }
+
+        self
+    }
+
+    ///
+    /// Return the width of the ray footprint in texture space.
+    ///
+    /// The footprint is converted with the smallest derivative, so that the texture is never
+    /// under-filtered. Intersections without derivatives have a null footprint.
+    ///
+    pub fn uv_footprint(&self) -> f32 {
+        let scale = self.dpdu.magn().min(self.dpdv.magn());
+
+        if scale > 0. {
+            self.footprint / scale
+        } else {
+            0.
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_frame(inter: &Intersection) {
+        let (n, t, b) = (inter.normal, inter.tangent, inter.bitangent);
+
+        assert!(n.is_normalized() && t.is_normalized() && b.is_normalized());
+        assert!(n.dot(t).abs() <= 1e-6);
+        assert!((n.cross(t) - b).magn() <= 1e-6);
+    }
+
+    #[test]
+    fn with_uv_test() {
+        let inter = Intersection::new(1., Point::ORIGIN, Vector::Z).with_uv(
+            (0.5, 0.5),
+            Vector::new(2., 0., 1.),
+            Vector::Y,
+        );
+
+        // The tangent follows `dpdu`, projected on the surface.
+        assert_frame(&inter);
+        assert!((inter.tangent - Vector::X).magn() <= 1e-6);
+    }
+
+    #[test]
+    fn with_shading_normal_test() {
+        let inter = Intersection::new(1., Point::ORIGIN, Vector::Z).with_uv(
+            (0.5, 0.5),
+            Vector::X,
+            Vector::Y,
+        );
+
+        let normal = Vector::new(1., 0., 1.).normalize();
+        let shaded = inter.with_shading_normal(normal);
+        assert_frame(&shaded);
+        assert_eq!(shaded.normal, normal);
+        assert_eq!(shaded.geometric_normal, Vector::Z);
+
+        // A normal along the tangent falls back on another tangent.
+        let shaded = inter.with_shading_normal(Vector::X);
+        assert_frame(&shaded);
+    }
+
+    #[test]
+    fn face_forward_test() {
+        let inter = Intersection::new(1., Point::ORIGIN, Vector::Z)
+            .with_uv((0.5, 0.5), Vector::X, Vector::Y)
+            .with_shading_normal(Vector::new(0., 1., 1.).normalize());
+
+        // Facing rays keep the intersection.
+        let front = inter.face_forward(-Vector::Z);
+        assert_eq!(front.normal, inter.normal);
+        assert_eq!(front.bitangent, inter.bitangent);
+
+        // Rays from behind flip the normals and the frame, which stays right-handed.
+        let back = inter.face_forward(Vector::Z);
+        assert_eq!(back.geometric_normal, -Vector::Z);
+        assert_eq!(back.normal, -inter.normal);
+        assert_frame(&back);
+    }
+}
@@ -0,0 +1,108 @@
+//!
+//! Progressive rendering.
+//!
+//! Instead of rendering all the samples of each pixel at once, a progressive render runs
+//! successive passes over the whole frame, each one adding a single sample per pixel into a
+//! persistent [Film]. The image can be read after any pass, and gets less noisy and less aliased
+//! as passes accumulate.
+//!
+
+use crate::colors::Color;
+
+use super::{Film, Filter};
+
+///
+/// State of a progressive render.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone)]
+pub struct Progressive {
+    film: Film,
+    passes: u32,
+}
+
+impl Progressive {
+    pub fn new(width: u32, height: u32, filter: Filter) -> Self {
+        Self {
+            film: Film::new(width, height, filter),
+            passes: 0,
+        }
+    }
+
+    ///
+    /// Return the number of passes accumulated so far.
+    ///
+    pub fn passes(&self) -> u32 {
+        self.passes
+    }
+
+    ///
+    /// Return the accumulation buffer, holding the average of all the passes so far.
+    ///
+    pub fn film(&self) -> &Film {
+        &self.film
+    }
+
+    ///
+    /// Add a sample, at the sub-pixel position `(x, y)`, to the current pass.
+    ///
+    pub fn add_sample(&mut self, x: f32, y: f32, color: Color) {
+        self.film.add_sample(x, y, color);
+    }
+
+    ///
+    /// Mark the current pass as complete.
+    ///
+    pub fn end_pass(&mut self) {
+        self.passes += 1;
+    }
+
+    ///
+    /// Drop all the accumulated samples, e.g. when the camera or the scene changed.
+    ///
+    pub fn reset(&mut self) {
+        self.film = Film::new(self.film.width(), self.film.height(), self.film.filter());
+        self.passes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulate_test() {
+        let mut progressive = Progressive::new(2, 1, Filter::default());
+        assert_eq!(progressive.passes(), 0);
+        assert_eq!(progressive.film().pixel(0, 0), Color::TRANSPARENT);
+
+        progressive.add_sample(0.5, 0.5, Color::new(1., 0., 0., 1.));
+        progressive.end_pass();
+        progressive.add_sample(0.25, 0.75, Color::new(0., 0., 1., 1.));
+        progressive.end_pass();
+
+        // Passes are averaged together, and only reach the pixels they sampled.
+        assert_eq!(progressive.passes(), 2);
+        assert_eq!(progressive.film().pixel(0, 0), Color::new(0.5, 0., 0.5, 1.));
+        assert_eq!(progressive.film().pixel(1, 0), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn reset_test() {
+        let filter = Filter::tent(1.);
+        let mut progressive = Progressive::new(3, 2, filter);
+        progressive.add_sample(1.5, 0.5, Color::WHITE);
+        progressive.end_pass();
+
+        progressive.reset();
+
+        assert_eq!(progressive.passes(), 0);
+        assert_eq!(progressive.film().pixel(1, 0), Color::TRANSPARENT);
+        assert_eq!(
+            (progressive.film().width(), progressive.film().height()),
+            (3, 2)
+        );
+        assert_eq!(progressive.film().filter(), filter);
+    }
+}
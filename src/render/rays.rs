@@ -8,6 +8,10 @@
 //! At their core, `Ray`s are defined as a [Point](crate::maths::Point) in space, and a unit
 //! [Vector](crate::maths::Vector).
 //!
+//! A `Ray` also carries a spread angle, i.e. it is the axis of a thin cone. Camera rays cover a
+//! pixel, so their spread grows the area they cover with distance, which is used to pick the level
+//! of detail of textures. Other rays have no spread.
+//!
 
 use crate::maths::{Point, Vector};
 
@@ -20,6 +24,8 @@ use crate::maths::{Point, Vector};
 pub struct Ray {
     pub orig: Point,
     pub dir: Vector,
+    /// Width covered by the ray at a unit distance from its origin.
+    pub spread: f32,
 }
 
 impl Ray {
@@ -31,6 +37,18 @@ impl Ray {
     ///
     pub fn new(orig: Point, dir: Vector) -> Self {
         debug_assert!(dir.is_normalized(), "Ray::new: dir is not a unit vector.");
-        Self { orig, dir }
+        Self {
+            orig,
+            dir,
+            spread: 0.,
+        }
+    }
+
+    ///
+    /// Set the width covered by the ray at a unit distance from its origin.
+    ///
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
     }
 }
@@ -0,0 +1,205 @@
+//!
+//! Render regions.
+//!
+//! A [Region] restricts rendering to a rectangle of the camera [Sensor](super::Sensor), to quickly
+//! iterate on a detail of a large frame. The projection is unchanged, and the pixels around the
+//! region are sampled as well, as far as the reconstruction [Filter](super::Filter) reaches: the
+//! region renders exactly as it would in the full frame.
+//!
+//! The rendered image is either cropped to the region, or keeps the size of the full frame, the
+//! pixels outside of the region being transparent.
+//!
+
+///
+/// Rectangle of the sensor to render.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// Column of the top-left pixel of the region.
+    x: u32,
+    /// Row of the top-left pixel of the region.
+    y: u32,
+    width: u32,
+    height: u32,
+    /// Whether the output image is cropped to the region.
+    crop: bool,
+}
+
+impl Region {
+    ///
+    /// Create a new `Region`, of `width` x `height` pixels, starting at the pixel `(x, y)`.
+    ///
+    /// The output keeps the size of the full frame, see [cropped](Self::cropped).
+    ///
+    /// # Panics:
+    /// Panics if `width` or `height` are not strictly positives integers.
+    ///
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        assert!(width > 0, "Region::new: width must be a positive integer.");
+        assert!(
+            height > 0,
+            "Region::new: height must be a positive integer."
+        );
+
+        Self {
+            x,
+            y,
+            width,
+            height,
+            crop: false,
+        }
+    }
+
+    ///
+    /// Region covering a whole frame of `width` x `height` pixels.
+    ///
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+            crop: false,
+        }
+    }
+
+    ///
+    /// Crop the output image to the region.
+    ///
+    pub fn cropped(mut self) -> Self {
+        self.crop = true;
+        self
+    }
+
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    ///
+    /// Check whether the output image is cropped to the region.
+    ///
+    pub fn is_cropped(&self) -> bool {
+        self.crop
+    }
+
+    ///
+    /// Check whether the pixel `(x, y)` lies in the region.
+    ///
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        (self.x..self.x.saturating_add(self.width)).contains(&x)
+            && (self.y..self.y.saturating_add(self.height)).contains(&y)
+    }
+
+    ///
+    /// Restrict the region to a frame of `width` x `height` pixels.
+    ///
+    /// The returned region may be empty, if it lies outside of the frame.
+    ///
+    pub fn clamp(self, width: u32, height: u32) -> Self {
+        let (x, y) = (self.x.min(width), self.y.min(height));
+
+        Self {
+            x,
+            y,
+            width: self.x.saturating_add(self.width).min(width) - x,
+            height: self.y.saturating_add(self.height).min(height) - y,
+            crop: self.crop,
+        }
+    }
+
+    ///
+    /// Grow the region by `margin` pixels on each side.
+    ///
+    /// The region doesn't grow past the pixel `(0, 0)`, see [clamp](Self::clamp) for the other
+    /// borders of the frame.
+    ///
+    pub fn padded(self, margin: u32) -> Self {
+        let (x, y) = (self.x.saturating_sub(margin), self.y.saturating_sub(margin));
+
+        Self {
+            x,
+            y,
+            width: self.x.saturating_add(self.width).saturating_add(margin) - x,
+            height: self.y.saturating_add(self.height).saturating_add(margin) - y,
+            crop: self.crop,
+        }
+    }
+
+    ///
+    /// Iterate over the pixels of the region, row by row.
+    ///
+    pub fn pixels(&self) -> impl Iterator<Item = (u32, u32)> {
+        let Self {
+            x,
+            width,
+            y,
+            height,
+            ..
+        } = *self;
+
+        (y..y.saturating_add(height))
+            .flat_map(move |y| (x..x.saturating_add(width)).map(move |x| (x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_test() {
+        let region = Region::new(2, 3, 4, 2);
+
+        assert!(region.contains(2, 3) && region.contains(5, 4));
+        assert!(!region.contains(1, 3) && !region.contains(6, 3));
+        assert!(!region.contains(2, 2) && !region.contains(2, 5));
+
+        // Regions reaching the end of the integers don't overflow.
+        let region = Region::new(u32::MAX - 1, u32::MAX - 1, 10, 10);
+        assert!(region.contains(u32::MAX - 1, u32::MAX - 1));
+        assert!(!region.contains(0, 0));
+    }
+
+    #[test]
+    fn clamp_test() {
+        let region = Region::new(6, 2, 4, 4).cropped().clamp(8, 4);
+        assert_eq!(region, Region::new(6, 2, 2, 2).cropped());
+
+        // Regions outside of the frame are empty.
+        let region = Region::new(10, 1, 4, 4).clamp(8, 4);
+        assert_eq!((region.width(), region.height()), (0, 3));
+        assert_eq!(region.pixels().count(), 0);
+
+        let region = Region::new(u32::MAX, 0, u32::MAX, 2).clamp(8, 4);
+        assert_eq!(region.width(), 0);
+    }
+
+    #[test]
+    fn padded_test() {
+        assert_eq!(Region::new(4, 4, 2, 2).padded(1), Region::new(3, 3, 4, 4));
+        assert_eq!(Region::new(1, 4, 2, 2).padded(3), Region::new(0, 1, 6, 8));
+        assert_eq!(Region::new(1, 1, 2, 2).padded(0), Region::new(1, 1, 2, 2));
+    }
+
+    #[test]
+    fn pixels_test() {
+        let pixels = Region::new(1, 2, 2, 2).pixels().collect::<Vec<_>>();
+
+        assert_eq!(pixels, [(1, 2), (2, 2), (1, 3), (2, 3)]);
+        assert_eq!(Region::full(5, 3).pixels().count(), 15);
+    }
+}
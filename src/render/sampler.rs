@@ -0,0 +1,154 @@
+//!
+//! Pixel samplers.
+//!
+//! Shooting a single [Ray](super::Ray) per pixel gives jagged edges, as a pixel is either fully
+//! covered by an object or not at all. A [Sampler] decides where, inside a pixel, several rays are
+//! shot. Their colors are then averaged to give the pixel color.
+//!
+
+use nannou::rand::Rng;
+
+///
+/// Sub-pixel sampling pattern.
+///
+/// Samples are returned as offsets from the pixel corner, in `[0, 1)`. Prefer the constructors to
+/// the variants, as they check that at least one sample is taken.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampler {
+    /// `n x n` samples, at the center of a regular grid.
+    Regular(u32),
+    /// `n x n` samples, each one randomly placed in a cell of a regular grid.
+    Jittered(u32),
+    /// `n` samples, randomly placed in the pixel.
+    Random(u32),
+}
+
+impl Sampler {
+    pub fn regular(n: u32) -> Self {
+        assert!(n > 0, "a sampler needs at least one sample (got 0)");
+        Self::Regular(n)
+    }
+
+    pub fn jittered(n: u32) -> Self {
+        assert!(n > 0, "a sampler needs at least one sample (got 0)");
+        Self::Jittered(n)
+    }
+
+    pub fn random(n: u32) -> Self {
+        assert!(n > 0, "a sampler needs at least one sample (got 0)");
+        Self::Random(n)
+    }
+
+    ///
+    /// Return the number of samples taken per pixel.
+    ///
+    pub fn count(&self) -> u32 {
+        match *self {
+            Self::Regular(n) | Self::Jittered(n) => n * n,
+            Self::Random(n) => n,
+        }
+    }
+
+    ///
+    /// Compute samples offsets, drawing random numbers from `rng`.
+    ///
+    /// Seeding `rng` from the pixel coordinates keeps renders of the same pixel identical.
+    ///
+    pub fn samples_with(&self, rng: &mut impl Rng) -> Vec<(f32, f32)> {
+        match *self {
+            Self::Regular(n) => Self::grid(n, || 0.5),
+            Self::Jittered(n) => Self::grid(n, || rng.gen()),
+            Self::Random(n) => (0..n).map(|_| (rng.gen(), rng.gen())).collect(),
+        }
+    }
+
+    ///
+    /// Build a `n x n` grid, placing each sample in its cell according to `offset`.
+    ///
+    fn grid(n: u32, mut offset: impl FnMut() -> f32) -> Vec<(f32, f32)> {
+        let step = (n.max(1) as f32).recip();
+
+        (0..n)
+            .flat_map(|j| (0..n).map(move |i| (i, j)))
+            .map(|(i, j)| ((i as f32 + offset()) * step, (j as f32 + offset()) * step))
+            .collect()
+    }
+}
+
+impl Default for Sampler {
+    ///
+    /// Default sampler shoots a single ray, through the pixel center.
+    ///
+    fn default() -> Self {
+        Self::regular(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nannou::rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    fn samples(sampler: Sampler, seed: u64) -> Vec<(f32, f32)> {
+        sampler.samples_with(&mut SmallRng::seed_from_u64(seed))
+    }
+
+    #[test]
+    fn count_test() {
+        for sampler in [
+            Sampler::regular(3),
+            Sampler::jittered(3),
+            Sampler::random(9),
+        ] {
+            assert_eq!(sampler.count(), 9);
+            assert_eq!(samples(sampler, 42).len(), 9);
+        }
+    }
+
+    #[test]
+    fn range_test() {
+        for sampler in [
+            Sampler::regular(4),
+            Sampler::jittered(4),
+            Sampler::random(16),
+        ] {
+            for (dx, dy) in samples(sampler, 73) {
+                assert!((0. ..1.).contains(&dx) && (0. ..1.).contains(&dy));
+            }
+        }
+    }
+
+    #[test]
+    fn regular_test() {
+        assert_eq!(samples(Sampler::default(), 0), vec![(0.5, 0.5)]);
+        assert_eq!(
+            samples(Sampler::regular(2), 0),
+            vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+        );
+    }
+
+    #[test]
+    fn jittered_test() {
+        // Each sample stays in its own cell of the grid.
+        for (k, (dx, dy)) in samples(Sampler::jittered(3), 15).into_iter().enumerate() {
+            assert_eq!((dx * 3.) as usize, k % 3);
+            assert_eq!((dy * 3.) as usize, k / 3);
+        }
+    }
+
+    #[test]
+    fn seeded_test() {
+        let sampler = Sampler::random(8);
+
+        assert_eq!(samples(sampler, 34), samples(sampler, 34));
+        assert_ne!(samples(sampler, 34), samples(sampler, 43));
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_test() {
+        Sampler::jittered(0);
+    }
+}
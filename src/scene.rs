@@ -6,10 +6,22 @@
 //!
 
 pub mod lights;
+pub mod material;
 pub mod objects;
+pub mod texture;
 
 pub use lights::Light;
+pub use material::{Material, Surface};
 pub use objects::Object;
+pub use texture::Texture;
+
+use nannou::rand::Rng;
+
+use crate::{
+    colors::Color,
+    maths::{sampling, Point, Vector},
+    render::{Intersection, Ray},
+};
 
 #[allow(dead_code)]
 pub struct Scene {
@@ -30,6 +42,98 @@ impl Scene {
     pub fn empty() -> Self {
         Self::new(vec![], vec![], 0.)
     }
+
+    ///
+    /// Find the closest intersection between a [Ray] and the scene objects.
+    ///
+    /// The shading normal of the intersection is perturbed by the object [Material] normal and
+    /// bump maps.
+    ///
+    pub fn intersect(&self, ray: Ray) -> Option<(Intersection, &dyn Object)> {
+        self.objects
+            .iter()
+            .filter_map(|o| o.intersect(ray).zip(Some(o.as_ref())))
+            .min_by(|(i, _), (i2, _)| i.dist.partial_cmp(&i2.dist).unwrap())
+            .map(|(inter, obj)| {
+                let footprint = ray.spread * inter.dist;
+                let inter = obj.material().perturb(Intersection { footprint, ..inter });
+
+                (inter, obj)
+            })
+    }
+
+    ///
+    /// Check whether any object lies on the [Ray], closer than `dist`.
+    ///
+    pub fn occluded(&self, ray: Ray, dist: f32) -> bool {
+        self.objects
+            .iter()
+            .any(|o| o.intersect(ray).is_some_and(|i| i.dist < dist))
+    }
+
+    ///
+    /// Return the light reaching a [Ray] escaping the scene in the direction `dir`, emitted by
+    /// the [lights] at infinity.
+    ///
+    /// The returned color is fully transparent, as it adds to the background rather than covering
+    /// it.
+    ///
+    pub fn emitted(&self, dir: Vector) -> Color {
+        let emitted = self
+            .lights
+            .iter()
+            .fold(Color::BLACK, |acc, l| acc + l.emitted(dir));
+
+        Color { a: 0., ..emitted }
+    }
+
+    ///
+    /// Compute the light coming straight from the [lights] and reflected at `pos` toward `wo`,
+    /// by a [Surface] of unit `normal`.
+    ///
+    /// Each light is sampled as many times as it asks for, and unshadowed samples are averaged.
+    /// `pos` should be slightly offset from the surface, so that shadow rays don't hit it.
+    ///
+    /// When `mis` is set, the caller also reaches the lights by sampling the surface BRDF, and
+    /// samples are weighted by multiple importance sampling against the BRDF density.
+    ///
+    pub fn direct_light(
+        &self,
+        pos: Point,
+        normal: Vector,
+        wo: Vector,
+        surface: &Surface,
+        mis: bool,
+        rng: &mut impl Rng,
+    ) -> Color {
+        let mut radiance = Color::BLACK;
+
+        for light in self.lights.iter() {
+            let samples = light.samples();
+
+            for _ in 0..samples {
+                let Some(sample) = light.sample(pos, (rng.gen(), rng.gen())) else {
+                    continue
+                };
+
+                let cos = normal.dot(sample.dir);
+                if cos > 0. && !self.occluded(Ray::new(pos, sample.dir), sample.dist) {
+                    let brdf = surface.brdf(normal, wo, sample.dir);
+                    let weight = match light.pdf(pos, sample.dir) {
+                        pdf if mis && pdf > 0. => sampling::power_heuristic(
+                            pdf * samples as f32,
+                            surface.pdf(normal, wo, sample.dir),
+                        ),
+                        _ => 1.,
+                    };
+
+                    radiance += brdf * sample.radiance * (weight * cos / samples as f32);
+                }
+            }
+        }
+
+        radiance
+    }
 }
 
 impl Default for Scene {
@@ -90,3 +194,62 @@ impl Default for SceneBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_1_PI;
+
+    use nannou::rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    /// White Lambertian surface at the origin, facing +Y.
+    fn surface() -> Surface {
+        Material::DEFAULT.surface(&Intersection::new(1., Point::ORIGIN, Vector::Y))
+    }
+
+    fn direct(scene: &Scene) -> Color {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let wo = Vector::new(0., 1., 1.).normalize();
+
+        scene.direct_light(Point::ORIGIN, Vector::Y, wo, &surface(), false, &mut rng)
+    }
+
+    #[test]
+    fn point_light_test() {
+        let light = lights::Omni::new(Point::new(0., 2., 2.), Color::WHITE, 8.);
+        let scene = scene().with_light(light).build();
+
+        // Inverse square falloff, and cosine at the surface.
+        let expected = FRAC_1_PI * 8. / 8. * (2. / 8f32.sqrt());
+        let radiance = direct(&scene);
+
+        assert!((radiance.r - expected).abs() <= 1e-5);
+        assert!((radiance.g - expected).abs() <= 1e-5);
+    }
+
+    #[test]
+    fn shadow_test() {
+        let light = lights::Omni::new(Point::new(0., 4., 0.), Color::WHITE, 1.);
+        let blocker = objects::Sphere::new(Point::new(0., 2., 0.), 0.5);
+        let blocked = scene().with_light(light).with_object(blocker).build();
+
+        assert_eq!(direct(&blocked).r, 0.);
+
+        // Lights behind the surface don't light it either.
+        let light = lights::Omni::new(Point::new(0., -4., 0.), Color::WHITE, 1.);
+        assert_eq!(direct(&scene().with_light(light).build()).r, 0.);
+    }
+
+    #[test]
+    fn area_light_test() {
+        // A Lambertian surface below a disk light reflects `L r² / (h² + r²)`.
+        let (h, r) = (3., 2.);
+        let light = lights::Disk::new(Point::new(0., h, 0.), -Vector::Y, r, Color::WHITE, 1.)
+            .with_samples(4096);
+        let scene = scene().with_light(light).build();
+        let expected = r * r / (h * h + r * r);
+
+        assert!((direct(&scene).r - expected).abs() <= 0.02 * expected);
+    }
+}
@@ -4,13 +4,82 @@
 //! Lights provide illumination (and thus shadows) to the scene.
 //!
 
+use crate::{
+    colors::Color,
+    maths::{Point, Vector},
+};
+
 pub mod directional;
+pub mod disk;
+pub mod environment;
+pub mod omni;
+pub mod rect;
+pub mod sphere;
+
 pub use directional::Directional;
+pub use disk::Disk;
+pub use environment::Environment;
+pub use omni::Omni;
+pub use rect::Rect;
+pub use sphere::Sphere;
+
+///
+/// Light reaching a point of the scene, as sampled by [Light::sample].
+///
+#[derive(Clone, Copy, Debug)]
+pub struct LightSample {
+    /// Unit vector pointing from the lit point toward the light.
+    pub dir: Vector,
+    /// Distance to the light, infinite for lights at infinity.
+    pub dist: f32,
+    /// Light reaching the point, ignoring occlusion.
+    pub radiance: Color,
+}
 
 ///
 /// Primary trait for lights.
 ///
-pub trait Light {
+pub trait Light: Send + Sync {
+    ///
+    /// Sample the light reaching `pos`.
+    ///
+    /// `u` is a pair of uniform random numbers in `[0, 1)`, used by lights with an area to pick a
+    /// point on their surface. The returned radiance is already divided by the probability of
+    /// picking this point, so that averaging several samples gives the light contribution.
+    ///
+    fn sample(&self, pos: Point, u: (f32, f32)) -> Option<LightSample>;
+
+    ///
+    /// Return the number of samples to average at each shaded point.
+    ///
+    /// Lights with an area need several samples to give smooth penumbrae, while a single sample
+    /// is exact for point-like lights.
+    ///
+    fn samples(&self) -> u32 {
+        1
+    }
+
+    ///
+    /// Return the probability density, over solid angle, of [sample](Self::sample) drawing the
+    /// direction `dir` from `pos`.
+    ///
+    /// Only lights which can be hit by rays, i.e. whose [emitted](Self::emitted) light isn't
+    /// black, need to implement it, so that both ways of reaching them can be weighted.
+    ///
+    fn pdf(&self, _pos: Point, _dir: Vector) -> f32 {
+        0.
+    }
+
+    ///
+    /// Return the light reaching a ray escaping the scene in the direction `dir`.
+    ///
+    /// Lights at infinity with an extent, such as environment lights, are seen by rays which
+    /// don't hit any object. Other lights are never hit and emit black.
+    ///
+    fn emitted(&self, _dir: Vector) -> Color {
+        Color::BLACK
+    }
+
     fn cloned(&self) -> Box<dyn Light>;
 }
 
@@ -19,3 +88,39 @@ impl Clone for Box<dyn Light> {
         self.cloned()
     }
 }
+
+///
+/// Sample the light emitted toward `pos` by the point `point` of a flat light of unit `normal`
+/// and total `area`, emitting `radiance` on the side of its normal.
+///
+/// Points are assumed to be drawn uniformly on the light surface.
+///
+fn area_sample(
+    pos: Point,
+    point: Point,
+    normal: Vector,
+    area: f32,
+    radiance: Color,
+) -> Option<LightSample> {
+    let to_light: Vector = point - pos;
+    let dist2 = to_light.magn2();
+
+    if dist2 == 0. {
+        return None;
+    }
+
+    let dist = dist2.sqrt();
+    let dir = to_light / dist;
+    let cos_light = -normal.dot(dir);
+
+    if cos_light <= 0. {
+        return None;
+    }
+
+    // Convert the area density `1 / area` to a solid angle density.
+    Some(LightSample {
+        dir,
+        dist,
+        radiance: radiance * (cos_light * area / dist2),
+    })
+}
@@ -5,14 +5,46 @@
 //! by this light will be parallels.
 //!
 
-use super::Light;
+use crate::{
+    colors::Color,
+    maths::{Point, Vector},
+};
+
+use super::{Light, LightSample};
 
 #[derive(Clone, Copy, Debug)]
-pub struct Directional {}
+pub struct Directional {
+    /// Direction the light travels in.
+    dir: Vector,
+    color: Color,
+    intensity: f32,
+}
 
-impl Directional {}
+impl Directional {
+    ///
+    /// Create a new directional light.
+    ///
+    /// `dir` is the direction the light travels in, e.g. `-Vector::Y` for a light shining
+    /// straight down.
+    ///
+    pub fn new(dir: Vector, color: Color, intensity: f32) -> Self {
+        Self {
+            dir: dir.normalize(),
+            color,
+            intensity,
+        }
+    }
+}
 
 impl Light for Directional {
+    fn sample(&self, _pos: Point, _u: (f32, f32)) -> Option<LightSample> {
+        Some(LightSample {
+            dir: -self.dir,
+            dist: f32::INFINITY,
+            radiance: self.color * self.intensity,
+        })
+    }
+
     fn cloned(&self) -> Box<dyn Light> {
         Box::new(*self)
     }
@@ -0,0 +1,109 @@
+//!
+//! Disk area [Light](super::Light).
+//!
+//! A disk light is a flat disk emitting light from one of its sides, like a spotlight lens. As it
+//! has an area, it casts soft shadows.
+//!
+
+use std::f32::consts::PI;
+
+use crate::{
+    colors::Color,
+    maths::{sampling, Point, Vector},
+};
+
+use super::{Light, LightSample};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Disk {
+    /// Center of the disk.
+    pos: Point,
+    /// Unit normal of the emitting side.
+    normal: Vector,
+    radius: f32,
+    color: Color,
+    intensity: f32,
+    samples: u32,
+}
+
+impl Disk {
+    ///
+    /// Create a new disk light, emitting light on the side of `normal`.
+    ///
+    /// `intensity` is the emitted radiance, so that the power of the light grows with its area.
+    ///
+    pub fn new(pos: Point, normal: Vector, radius: f32, color: Color, intensity: f32) -> Self {
+        Self {
+            pos,
+            normal: normal.normalize(),
+            radius,
+            color,
+            intensity,
+            samples: 1,
+        }
+    }
+
+    ///
+    /// Set the number of shadow rays shot toward this light from each shaded point.
+    ///
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+}
+
+impl Light for Disk {
+    fn sample(&self, pos: Point, u: (f32, f32)) -> Option<LightSample> {
+        let (x, y) = sampling::concentric_disk(u);
+        let (t, b) = self.normal.any_orthonormal_pair();
+        let point = self.pos + (t * x + b * y) * self.radius;
+        let area = PI * self.radius * self.radius;
+
+        super::area_sample(pos, point, self.normal, area, self.color * self.intensity)
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    fn cloned(&self) -> Box<dyn Light> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_position_test() {
+        let light = Disk::new(Point::new(1., 4., 0.), -Vector::Y, 2., Color::WHITE, 1.);
+
+        for u in [(0., 0.), (0.3, 0.9), (0.99, 0.5), (0.5, 0.5)] {
+            let s = light.sample(Point::ORIGIN, u).unwrap();
+            let p = Point::ORIGIN + s.dir * s.dist;
+
+            assert!((p.y - 4.).abs() <= 1e-5);
+            assert!((p - Point::new(1., 4., 0.)).magn() <= 2. + 1e-5);
+        }
+
+        assert!(light.sample(Point::new(0., 5., 0.), (0.5, 0.5)).is_none());
+    }
+
+    #[test]
+    fn irradiance_test() {
+        // Irradiance below the center of a disk is `π L r² / (h² + r²)`.
+        let (h, r) = (3., 2.);
+        let light = Disk::new(Point::new(0., h, 0.), -Vector::Y, r, Color::WHITE, 1.);
+        let n = 64;
+        let estimate = (0..n * n)
+            .map(|i| ((i % n) as f32 + 0.5, (i / n) as f32 + 0.5))
+            .filter_map(|(u, v)| light.sample(Point::ORIGIN, (u / n as f32, v / n as f32)))
+            .map(|s| s.radiance.r * s.dir.y)
+            .sum::<f32>()
+            / (n * n) as f32;
+        let expected = PI * r * r / (h * h + r * r);
+
+        assert!((estimate - expected).abs() <= 1e-2 * expected, "{estimate}");
+    }
+}
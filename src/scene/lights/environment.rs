@@ -0,0 +1,322 @@
+//!
+//! Environment [Light](super::Light).
+//!
+//! An environment light surrounds the whole scene, like the sky or a studio, with light coming
+//! from infinitely far away. Its radiance for each direction is read from an equirectangular
+//! image, usually a high dynamic range (Radiance `.hdr`) capture of a real place.
+//!
+//! In the image, columns map to the azimuth around the `Y` axis, the center of the image facing
+//! `+Z`, and rows map to the polar angle, the top row facing `+Y`.
+//!
+//! The environment is importance sampled: bright areas, such as the sun or windows, are sampled
+//! more often than dark ones, which greatly reduces noise.
+//!
+
+use std::{f32::consts::PI, fs::File, io::BufReader, path::Path, sync::Arc};
+
+use nannou::image::{codecs::hdr::HdrDecoder, ImageResult};
+
+use crate::{
+    colors::Color,
+    maths::{distribution::Distribution2D, Point, Vector},
+};
+
+use super::{Light, LightSample};
+
+#[derive(Clone, Debug)]
+pub struct Environment {
+    width: usize,
+    height: usize,
+    /// Linear radiance of each pixel, row by row.
+    pixels: Arc<[Color]>,
+    intensity: f32,
+    distribution: Arc<Distribution2D>,
+    samples: u32,
+}
+
+impl Environment {
+    ///
+    /// Create a new environment light, from a `width × height` equirectangular image of linear
+    /// colors, stored row by row.
+    ///
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>, intensity: f32) -> Self {
+        assert_eq!(pixels.len(), width * height);
+
+        // Rows near the poles cover a smaller solid angle, so they are less likely to be picked.
+        let weights: Vec<f32> = pixels
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let theta = PI * ((i / width) as f32 + 0.5) / height as f32;
+                (0.2126 * c.r + 0.7152 * c.g + 0.0722 * c.b) * theta.sin()
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels: pixels.into(),
+            intensity,
+            distribution: Arc::new(Distribution2D::new(width, height, &weights)),
+            samples: 1,
+        }
+    }
+
+    ///
+    /// Load an environment light from an equirectangular Radiance `.hdr` image.
+    ///
+    pub fn from_hdr(path: impl AsRef<Path>, intensity: f32) -> ImageResult<Self> {
+        let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+        let meta = decoder.metadata();
+        let pixels = decoder
+            .read_image_hdr()?
+            .into_iter()
+            .map(|px| Color::from_rgb(px.0[0], px.0[1], px.0[2]))
+            .collect();
+
+        Ok(Self::new(
+            meta.width as usize,
+            meta.height as usize,
+            pixels,
+            intensity,
+        ))
+    }
+
+    ///
+    /// Set the number of shadow rays shot toward this light from each shaded point.
+    ///
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    ///
+    /// Return the radiance coming from the direction `dir`.
+    ///
+    pub fn radiance(&self, dir: Vector) -> Color {
+        let (u, v) = Self::dir_to_uv(dir.normalize());
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+
+        self.pixels[y * self.width + x] * self.intensity
+    }
+
+    fn dir_to_uv(dir: Vector) -> (f32, f32) {
+        let phi = dir.x.atan2(dir.z);
+        let theta = dir.y.clamp(-1., 1.).acos();
+
+        (0.5 + phi / (2. * PI), theta / PI)
+    }
+
+    ///
+    /// Return the direction of the point `(u, v)` of an equirectangular image, both coordinates
+    /// being in `[0, 1]` from the top-left corner.
+    ///
+    pub(crate) fn uv_to_dir((u, v): (f32, f32)) -> Vector {
+        let phi = (u - 0.5) * 2. * PI;
+        let theta = v * PI;
+
+        Vector::new(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            theta.sin() * phi.cos(),
+        )
+    }
+
+    ///
+    /// Convert a density over the image to a density over directions.
+    ///
+    fn uv_pdf_to_dir_pdf(pdf: f32, v: f32) -> f32 {
+        let sin = (v * PI).sin();
+
+        if sin <= 0. {
+            0.
+        } else {
+            pdf / (2. * PI * PI * sin)
+        }
+    }
+}
+
+impl Light for Environment {
+    fn sample(&self, _pos: Point, u: (f32, f32)) -> Option<LightSample> {
+        let (uv, pdf) = self.distribution.sample(u);
+        let pdf = Self::uv_pdf_to_dir_pdf(pdf, uv.1);
+
+        if pdf <= 0. {
+            return None;
+        }
+
+        let dir = Self::uv_to_dir(uv);
+
+        Some(LightSample {
+            dir,
+            dist: f32::INFINITY,
+            radiance: self.radiance(dir) / pdf,
+        })
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    fn pdf(&self, _pos: Point, dir: Vector) -> f32 {
+        let uv = Self::dir_to_uv(dir.normalize());
+
+        Self::uv_pdf_to_dir_pdf(self.distribution.pdf(uv), uv.1)
+    }
+
+    fn emitted(&self, dir: Vector) -> Color {
+        self.radiance(dir)
+    }
+
+    fn cloned(&self) -> Box<dyn Light> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use nannou::image::{codecs::hdr::HdrEncoder, Rgb};
+
+    use super::*;
+
+    ///
+    /// `width × height` map of uniform radiance.
+    ///
+    fn uniform(width: usize, height: usize) -> Environment {
+        Environment::new(width, height, vec![Color::WHITE; width * height], 1.)
+    }
+
+    ///
+    /// Map whose red channel is the index of each pixel, plus one.
+    ///
+    fn indexed(width: usize, height: usize) -> Environment {
+        let pixels = (0..width * height)
+            .map(|i| Color::from_rgb(i as f32 + 1., 1., 1.))
+            .collect();
+
+        Environment::new(width, height, pixels, 2.)
+    }
+
+    ///
+    /// Grid of `n × n` pairs of numbers at the center of cells of `[0, 1)²`.
+    ///
+    fn grid(n: usize) -> impl Iterator<Item = (f32, f32)> {
+        let at = move |i: usize| (i as f32 + 0.5) / n as f32;
+
+        (0..n).flat_map(move |i| (0..n).map(move |j| (at(i), at(j))))
+    }
+
+    #[test]
+    fn uv_round_trip_test() {
+        for (u, v) in grid(16) {
+            let dir = Environment::uv_to_dir((u, v));
+            let (u2, v2) = Environment::dir_to_uv(dir);
+
+            assert!(dir.is_normalized());
+            assert!((u - u2).abs() <= 1e-5 && (v - v2).abs() <= 1e-5, "{u}, {v}");
+        }
+
+        // The center of the map faces `+Z`, and the top row `+Y`.
+        assert!((Environment::uv_to_dir((0.5, 0.5)) - Vector::Z).magn() <= 1e-6);
+        assert!((Environment::uv_to_dir((0.75, 0.5)) - Vector::X).magn() <= 1e-6);
+        assert!((Environment::uv_to_dir((0.3, 0.)) - Vector::Y).magn() <= 1e-6);
+    }
+
+    #[test]
+    fn radiance_test() {
+        let light = indexed(4, 3);
+        let red = |dir: Vector| light.radiance(dir).r / 2. - 1.;
+
+        // Columns go around `Y` from `-Z`, through `-X`, `+Z` and `+X`, on the middle row.
+        assert_eq!(red(Vector::new(-0.01, 0., -1.)), 4.);
+        assert_eq!(red(-Vector::X), 5.);
+        assert_eq!(red(Vector::Z), 6.);
+        assert_eq!(red(Vector::X), 7.);
+        assert_eq!(red(Vector::new(0.01, 0., -1.)), 7.);
+
+        // Rows go from `+Y` to `-Y`.
+        assert_eq!(red(Vector::new(0., 1., 0.01)), 2.);
+        assert_eq!(red(Vector::new(0., -1., 0.01)), 10.);
+
+        assert_eq!(light.emitted(Vector::Z), light.radiance(Vector::Z));
+    }
+
+    #[test]
+    fn sample_pdf_test() {
+        let light = indexed(16, 8);
+
+        // The density of the sampled directions is the one given by `pdf`.
+        for u in grid(12) {
+            let sample = light.sample(Point::ORIGIN, u).unwrap();
+            let pdf = light.radiance(sample.dir).r / sample.radiance.r;
+
+            assert_eq!(sample.dist, f32::INFINITY);
+            let expected = light.pdf(Point::ORIGIN, sample.dir);
+            assert!(
+                (pdf - expected).abs() <= 1e-3 * expected,
+                "{pdf} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn uniform_sample_test() {
+        let light = uniform(64, 128);
+
+        // Directions are sampled following the solid angle, so every sample carries the same
+        // light, up to the variation of the solid angle within each row of the map.
+        for u in grid(20) {
+            let sample = light.sample(Point::ORIGIN, u).unwrap();
+
+            if sample.dir.y.abs() < 0.9 {
+                assert!((sample.radiance.r - 4. * PI).abs() <= 0.03 * 4. * PI);
+            }
+        }
+    }
+
+    #[test]
+    fn pdf_integral_test() {
+        let light = uniform(32, 16);
+        let n = 256;
+
+        // Integrate the density over the sphere, using the Jacobian of the mapping.
+        let integral = grid(n)
+            .map(|(u, v)| {
+                let dir = Environment::uv_to_dir((u, v));
+                light.pdf(Point::ORIGIN, dir) * 2. * PI * PI * (v * PI).sin()
+            })
+            .sum::<f32>()
+            / (n * n) as f32;
+        assert!((integral - 1.).abs() <= 1e-3);
+
+        // Along the equator, the density is the one of uniform directions.
+        let pdf = light.pdf(Point::ORIGIN, Vector::new(0.3, 0., 1.));
+        assert!((pdf - 1. / (4. * PI)).abs() <= 1e-2 / (4. * PI));
+    }
+
+    #[test]
+    fn samples_test() {
+        assert_eq!(uniform(2, 1).with_samples(0).samples(), 1);
+        assert_eq!(uniform(2, 1).with_samples(8).samples(), 8);
+    }
+
+    #[test]
+    fn from_hdr_test() {
+        let path = std::env::temp_dir().join(format!("environment-{}.hdr", std::process::id()));
+        let pixels = [[0.5, 1., 2.], [4., 0.25, 1.]].map(Rgb);
+        HdrEncoder::new(File::create(&path).unwrap())
+            .encode(&pixels, 2, 1)
+            .unwrap();
+
+        let light = Environment::from_hdr(&path, 3.).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((light.width, light.height), (2, 1));
+        let rgb = |c: Color| [c.r, c.g, c.b];
+        assert_eq!(rgb(light.radiance(-Vector::X)), [1.5, 3., 6.]);
+        assert_eq!(rgb(light.radiance(Vector::X)), [12., 0.75, 3.]);
+    }
+}
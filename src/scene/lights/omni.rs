@@ -0,0 +1,51 @@
+//!
+//! Omnidirectional [Light](super::Light).
+//!
+//! An omni light is a point in space, emitting light equally in all directions. Its intensity
+//! decreases with the square of the distance.
+//!
+
+use crate::{
+    colors::Color,
+    maths::{Point, Vector},
+};
+
+use super::{Light, LightSample};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Omni {
+    pos: Point,
+    color: Color,
+    intensity: f32,
+}
+
+impl Omni {
+    pub fn new(pos: Point, color: Color, intensity: f32) -> Self {
+        Self {
+            pos,
+            color,
+            intensity,
+        }
+    }
+}
+
+impl Light for Omni {
+    fn sample(&self, pos: Point, _u: (f32, f32)) -> Option<LightSample> {
+        let to_light: Vector = self.pos - pos;
+        let dist2 = to_light.magn2();
+
+        if dist2 == 0. {
+            return None;
+        }
+
+        Some(LightSample {
+            dir: to_light.normalize(),
+            dist: dist2.sqrt(),
+            radiance: self.color * (self.intensity / dist2),
+        })
+    }
+
+    fn cloned(&self) -> Box<dyn Light> {
+        Box::new(*self)
+    }
+}
@@ -0,0 +1,145 @@
+//!
+//! Rectangular area [Light](super::Light).
+//!
+//! A rect light is a parallelogram emitting light from one of its sides, like a softbox or a
+//! window. As it has an area, it casts soft shadows.
+//!
+
+use crate::{
+    colors::Color,
+    maths::{Point, Vector},
+};
+
+use super::{Light, LightSample};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Rect {
+    /// Corner of the rectangle.
+    pos: Point,
+    /// First edge, starting from `pos`.
+    edge_u: Vector,
+    /// Second edge, starting from `pos`.
+    edge_v: Vector,
+    /// Unit normal of the emitting side.
+    normal: Vector,
+    area: f32,
+    color: Color,
+    intensity: f32,
+    samples: u32,
+}
+
+impl Rect {
+    ///
+    /// Create a new rect light, of corner `pos` and edges `edge_u` and `edge_v`.
+    ///
+    /// Light is emitted on the side of `edge_u × edge_v`. `intensity` is the emitted radiance,
+    /// so that the power of the light grows with its area.
+    ///
+    pub fn new(pos: Point, edge_u: Vector, edge_v: Vector, color: Color, intensity: f32) -> Self {
+        let cross = edge_u.cross(edge_v);
+
+        Self {
+            pos,
+            edge_u,
+            edge_v,
+            normal: cross.normalize(),
+            area: cross.magn(),
+            color,
+            intensity,
+            samples: 1,
+        }
+    }
+
+    ///
+    /// Set the number of shadow rays shot toward this light from each shaded point.
+    ///
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+}
+
+impl Light for Rect {
+    fn sample(&self, pos: Point, (u, v): (f32, f32)) -> Option<LightSample> {
+        let point = self.pos + self.edge_u * u + self.edge_v * v;
+
+        super::area_sample(
+            pos,
+            point,
+            self.normal,
+            self.area,
+            self.color * self.intensity,
+        )
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    fn cloned(&self) -> Box<dyn Light> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    /// Light emitting downward, spanning `[-1, 1] x [-2, 2]` at `y = 3`.
+    fn light() -> Rect {
+        Rect::new(
+            Point::new(-1., 3., -2.),
+            Vector::new(2., 0., 0.),
+            Vector::new(0., 0., 4.),
+            Color::WHITE,
+            1.,
+        )
+    }
+
+    #[test]
+    fn sample_position_test() {
+        let light = light();
+
+        for u in [(0., 0.), (0.3, 0.9), (0.99, 0.5)] {
+            let s = light.sample(Point::ORIGIN, u).unwrap();
+            let p = Point::ORIGIN + s.dir * s.dist;
+
+            assert!((p.y - 3.).abs() <= 1e-5);
+            assert!((-1. ..=1.).contains(&p.x) && (-2. ..=2.).contains(&p.z));
+            assert!((p.x - (-1. + 2. * u.0)).abs() <= 1e-5);
+            assert!((p.z - (-2. + 4. * u.1)).abs() <= 1e-5);
+        }
+
+        // Nothing is emitted toward the back side.
+        assert!(light.sample(Point::new(0., 5., 0.), (0.5, 0.5)).is_none());
+    }
+
+    #[test]
+    fn pdf_test() {
+        // The radiance is divided by the solid angle density `dist² / (cos * area)`.
+        let light = light();
+        let s = light.sample(Point::ORIGIN, (0.25, 0.75)).unwrap();
+        let pdf = s.dist * s.dist / (s.dir.y * 8.);
+
+        assert!((s.radiance.r * pdf - 1.).abs() <= 1e-5);
+    }
+
+    #[test]
+    fn solid_angle_test() {
+        // Averaging radiance / L estimates the solid angle covered by the light.
+        let light = light();
+        let n = 64;
+        let estimate = (0..n * n)
+            .map(|i| ((i % n) as f32 + 0.5, (i / n) as f32 + 0.5))
+            .filter_map(|(u, v)| light.sample(Point::ORIGIN, (u / n as f32, v / n as f32)))
+            .map(|s| s.radiance.r)
+            .sum::<f32>()
+            / (n * n) as f32;
+        let (a, b, d): (f32, f32, f32) = (2., 4., 3.);
+        let expected = 4. * (a * b / ((a * a + 4. * d * d) * (b * b + 4. * d * d)).sqrt()).asin();
+
+        assert!((estimate - expected).abs() <= 1e-3 * 4. * PI);
+    }
+}
@@ -0,0 +1,144 @@
+//!
+//! Spherical area [Light](super::Light).
+//!
+//! A sphere light is a ball emitting light uniformly from its whole surface, like a light bulb.
+//! As it has an area, it casts soft shadows.
+//!
+//! Seen from a point outside of it, a sphere covers a cone of directions. Sampling directions
+//! uniformly in that cone only picks visible points of the sphere, which gives less noise than
+//! sampling its whole surface.
+//!
+
+use std::f32::consts::PI;
+
+use crate::{
+    colors::Color,
+    maths::{Point, Vector},
+};
+
+use super::{Light, LightSample};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sphere {
+    pos: Point,
+    radius: f32,
+    color: Color,
+    intensity: f32,
+    samples: u32,
+}
+
+impl Sphere {
+    ///
+    /// Create a new sphere light.
+    ///
+    /// `intensity` is the emitted radiance, so that the power of the light grows with its area.
+    ///
+    pub fn new(pos: Point, radius: f32, color: Color, intensity: f32) -> Self {
+        Self {
+            pos,
+            radius,
+            color,
+            intensity,
+            samples: 1,
+        }
+    }
+
+    ///
+    /// Set the number of shadow rays shot toward this light from each shaded point.
+    ///
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+}
+
+impl Light for Sphere {
+    fn sample(&self, pos: Point, (u, v): (f32, f32)) -> Option<LightSample> {
+        let to_center: Vector = self.pos - pos;
+        let dist2 = to_center.magn2();
+        let r2 = self.radius * self.radius;
+
+        // Points inside the sphere are not lit.
+        if dist2 <= r2 {
+            return None;
+        }
+
+        let dist = dist2.sqrt();
+        let axis = to_center / dist;
+        let cos_max = (1. - r2 / dist2).max(0.).sqrt();
+
+        let cos = 1. - u * (1. - cos_max);
+        let sin = (1. - cos * cos).max(0.).sqrt();
+        let phi = 2. * PI * v;
+        let (t, b) = axis.any_orthonormal_pair();
+        let dir = (t * (sin * phi.cos()) + b * (sin * phi.sin()) + axis * cos).normalize();
+
+        // Distance to the near side of the sphere along `dir`.
+        let hit = dist * cos - (r2 - dist2 * sin * sin).max(0.).sqrt();
+        let solid_angle = 2. * PI * (1. - cos_max);
+
+        Some(LightSample {
+            dir,
+            dist: hit,
+            radiance: self.color * (self.intensity * solid_angle),
+        })
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    fn cloned(&self) -> Box<dyn Light> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_position_test() {
+        let center = Point::new(0., 0., 5.);
+        let light = Sphere::new(center, 1., Color::WHITE, 1.);
+
+        for u in [(0., 0.), (0.3, 0.9), (0.99, 0.5), (0.5, 0.5)] {
+            let s = light.sample(Point::ORIGIN, u).unwrap();
+            let p = Point::ORIGIN + s.dir * s.dist;
+
+            // Sampled points lie on the visible side of the sphere.
+            assert!(((p - center).magn() - 1.).abs() <= 1e-4);
+            assert!((p - center).dot(s.dir) <= 1e-4);
+        }
+
+        assert!(light.sample(Point::new(0., 0.5, 5.), (0.5, 0.5)).is_none());
+    }
+
+    #[test]
+    fn pdf_test() {
+        // Directions are uniform in the cone, so each sample carries `L * solid angle`.
+        let (d, r) = (5., 1.);
+        let light = Sphere::new(Point::new(0., 0., d), r, Color::WHITE, 2.);
+        let s = light.sample(Point::ORIGIN, (0.3, 0.6)).unwrap();
+        let cos_max = (1. - r * r / (d * d)).sqrt();
+
+        assert!((s.radiance.r - 2. * 2. * PI * (1. - cos_max)).abs() <= 1e-4);
+    }
+
+    #[test]
+    fn irradiance_test() {
+        // Irradiance facing a sphere is `π L (r / d)²`.
+        let (d, r) = (4., 1.5);
+        let light = Sphere::new(Point::new(0., 0., d), r, Color::WHITE, 1.);
+        let n = 64;
+        let estimate = (0..n * n)
+            .map(|i| ((i % n) as f32 + 0.5, (i / n) as f32 + 0.5))
+            .filter_map(|(u, v)| light.sample(Point::ORIGIN, (u / n as f32, v / n as f32)))
+            .map(|s| s.radiance.r * s.dir.z)
+            .sum::<f32>()
+            / (n * n) as f32;
+        let expected = PI * (r / d).powi(2);
+
+        assert!((estimate - expected).abs() <= 1e-2 * expected, "{estimate}");
+    }
+}
@@ -0,0 +1,600 @@
+//!
+//! Surface materials.
+//!
+//! A [Material] describes how the surface of an [Object](super::Object) interacts with light.
+//!
+//! Materials follow the metallic-roughness model of standard PBR pipelines (e.g. glTF), so that
+//! assets authored for those render the same way:
+//! - Dielectrics (`metallic = 0`) diffusely reflect their base color, with a faint white specular
+//!   reflection on top.
+//! - Metals (`metallic = 1`) have no diffuse reflection, and a specular reflection tinted by their
+//!   base color.
+//!
+//! The specular reflection of dielectrics is opt-in (see [pbr](Material::pbr)): other materials,
+//! including the [default](Material::DEFAULT) one, are purely Lambertian.
+//!
+//! Each channel is a constant factor, which can be multiplied by a [Texture]. The textures are
+//! evaluated at each hit point, giving the [Surface] properties on which the BRDF is computed.
+//!
+//! Materials may also add surface details without adding geometry, by perturbing the shading
+//! normal (see [perturb](Material::perturb)):
+//! - A normal map stores tangent space normals, each channel being mapped from `[0, 1]` to
+//!   `[-1, 1]`. The tangent frame is the one of the [Intersection].
+//! - A bump map stores heights, the mean of its channels, of which the normal follows the slope.
+//!   Any texture can be used as a bump map, e.g. a [Procedural](super::texture::Procedural)
+//!   noise.
+//!
+//! The specular reflection is a Cook-Torrance microfacet BRDF, built from the GGX distribution,
+//! the Smith masking-shadowing term and the Schlick Fresnel approximation (see
+//! [microfacet](crate::maths::microfacet)).
+//!
+
+use std::f32::consts::FRAC_1_PI;
+
+use crate::{
+    colors::Color,
+    maths::{microfacet, sampling, Vector},
+    render::Intersection,
+};
+
+use super::Texture;
+
+/// Reflectance at normal incidence of dielectrics.
+const DIELECTRIC_F0: f32 = 0.04;
+
+///
+/// Surface material.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    /// Base color: diffuse albedo of dielectrics, and specular reflectance of metals.
+    pub color: Color,
+    /// Light emitted by the surface itself, which turns the object into a light source.
+    pub emission: Color,
+    /// Metalness, from `0` (dielectric) to `1` (metal).
+    pub metallic: f32,
+    /// Perceptual roughness, from `0` (mirror-like) to `1` (fully rough).
+    pub roughness: f32,
+    /// Strength of the specular reflection of dielectrics, from `0` (Lambertian) to `1`.
+    pub specular: f32,
+    /// Texture multiplying the base color.
+    pub color_map: Option<Texture>,
+    /// Texture multiplying the roughness (green channel) and the metalness (blue channel).
+    pub metallic_roughness_map: Option<Texture>,
+    /// Texture multiplying the emission.
+    pub emission_map: Option<Texture>,
+    /// Tangent space normal map.
+    pub normal_map: Option<Texture>,
+    /// Height map.
+    pub bump_map: Option<Texture>,
+    /// Height of the bumps, in world units, for a unit value of the bump map.
+    pub bump_scale: f32,
+    /// Fraction of the light going through the surface, from `0` (opaque) to `1` (clear glass).
+    pub transmission: f32,
+    /// Index of refraction of the inside of the object.
+    pub ior: f32,
+}
+
+impl Material {
+    pub const fn new(color: Color, emission: Color) -> Self {
+        Self {
+            color,
+            emission,
+            metallic: 0.,
+            roughness: 1.,
+            specular: 0.,
+            color_map: None,
+            metallic_roughness_map: None,
+            emission_map: None,
+            normal_map: None,
+            bump_map: None,
+            bump_scale: 1.,
+            transmission: 0.,
+            ior: 1.5,
+        }
+    }
+
+    ///
+    /// Lambertian, non-emissive material.
+    ///
+    pub const fn diffuse(color: Color) -> Self {
+        Self::new(color, Color::BLACK)
+    }
+
+    ///
+    /// Emissive material, which doesn't reflect any light.
+    ///
+    pub const fn emissive(emission: Color) -> Self {
+        Self::new(Color::BLACK, emission)
+    }
+
+    ///
+    /// Non-emissive metallic-roughness material.
+    ///
+    /// Unlike the other constructors, dielectrics get a specular reflection.
+    ///
+    pub const fn pbr(color: Color, metallic: f32, roughness: f32) -> Self {
+        Self {
+            color,
+            emission: Color::BLACK,
+            metallic,
+            roughness,
+            specular: 1.,
+            color_map: None,
+            metallic_roughness_map: None,
+            emission_map: None,
+            normal_map: None,
+            bump_map: None,
+            bump_scale: 1.,
+            transmission: 0.,
+            ior: 1.5,
+        }
+    }
+
+    ///
+    /// Default material, a white Lambertian surface.
+    ///
+    pub const DEFAULT: Material = Material::diffuse(Color::WHITE);
+
+    pub fn with_color_map(mut self, map: impl Into<Texture>) -> Self {
+        self.color_map = Some(map.into());
+        self
+    }
+
+    ///
+    /// Set a texture driving the roughness (green channel) and the metalness (blue channel),
+    /// following the glTF convention.
+    ///
+    pub fn with_metallic_roughness_map(mut self, map: impl Into<Texture>) -> Self {
+        self.metallic_roughness_map = Some(map.into());
+        self
+    }
+
+    pub fn with_emission_map(mut self, map: impl Into<Texture>) -> Self {
+        self.emission_map = Some(map.into());
+        self
+    }
+
+    ///
+    /// Set a tangent space normal map. Normal maps should be loaded as linear textures.
+    ///
+    pub fn with_normal_map(mut self, map: impl Into<Texture>) -> Self {
+        self.normal_map = Some(map.into());
+        self
+    }
+
+    ///
+    /// Set a bump map, whose values are scaled by `scale` to give heights in world units.
+    ///
+    pub fn with_bump_map(mut self, map: impl Into<Texture>, scale: f32) -> Self {
+        self.bump_map = Some(map.into());
+        self.bump_scale = scale;
+        self
+    }
+
+    ///
+    /// Let a fraction `transmission` of the light go through the surface, refracted following
+    /// the index of refraction `ior` of the inside of the object.
+    ///
+    /// Transmission is only followed by the [Whitted](crate::render::integrator::Whitted)
+    /// integrator, other integrators render the surface as opaque.
+    ///
+    pub fn with_transmission(mut self, transmission: f32, ior: f32) -> Self {
+        self.transmission = transmission;
+        self.ior = ior;
+        self
+    }
+
+    ///
+    /// Perturb the shading normal of an [Intersection] following the normal map, then the bump
+    /// map of the material.
+    ///
+    /// Surfaces without texture coordinates derivatives can't be bump mapped, and are returned
+    /// unchanged by the bump map.
+    ///
+    pub fn perturb(&self, inter: Intersection) -> Intersection {
+        let mut inter = inter;
+
+        if let Some(map) = &self.normal_map {
+            let c = map.eval(&inter);
+            let normal = inter.tangent * (2. * c.r - 1.)
+                + inter.bitangent * (2. * c.g - 1.)
+                + inter.normal * (2. * c.b - 1.);
+
+            if normal.magn2() > 1e-12 {
+                inter = inter.with_shading_normal(normal.normalize());
+            }
+        }
+
+        if let Some(map) = &self.bump_map {
+            if let Some(normal) = self.bump(map, &inter) {
+                inter = inter.with_shading_normal(normal);
+            }
+        }
+
+        inter
+    }
+
+    ///
+    /// Compute the normal of the surface displaced by the bump map, from finite differences of
+    /// the heights along `u` and `v`.
+    ///
+    fn bump(&self, map: &Texture, inter: &Intersection) -> Option<Vector> {
+        let height = |inter: &Intersection| {
+            let c = map.eval(inter);
+            (c.r + c.g + c.b) / 3. * self.bump_scale
+        };
+        let (u, v) = inter.uv;
+        let n = inter.normal;
+        // Differences are taken over the ray footprint, so that bumps are filtered like textures.
+        let delta = (0.5 * inter.uv_footprint()).max(1e-4);
+
+        let h = height(inter);
+        let du = Intersection {
+            pos: inter.pos + inter.dpdu * delta,
+            uv: (u + delta, v),
+            ..*inter
+        };
+        let dv = Intersection {
+            pos: inter.pos + inter.dpdv * delta,
+            uv: (u, v + delta),
+            ..*inter
+        };
+
+        let dpdu = inter.dpdu + n * ((height(&du) - h) / delta);
+        let dpdv = inter.dpdv + n * ((height(&dv) - h) / delta);
+        let normal = dpdu.cross(dpdv);
+
+        if normal.magn2() <= 1e-12 {
+            return None;
+        }
+        let normal = normal.normalize();
+
+        Some(if normal.dot(n) < 0. { -normal } else { normal })
+    }
+
+    ///
+    /// Evaluate the textures of the material at an [Intersection], giving the [Surface]
+    /// properties at this point.
+    ///
+    /// Texture values are multiplied by the constant factors of the material.
+    ///
+    pub fn surface(&self, inter: &Intersection) -> Surface {
+        let eval = |map: &Option<Texture>| map.as_ref().map(|t| t.eval(inter));
+
+        let color = eval(&self.color_map).map_or(self.color, |c| self.color * c);
+        let emission = eval(&self.emission_map).map_or(self.emission, |c| self.emission * c);
+        let (metallic, roughness) = eval(&self.metallic_roughness_map)
+            .map_or((self.metallic, self.roughness), |c| {
+                (self.metallic * c.b, self.roughness * c.g)
+            });
+
+        Surface {
+            color,
+            emission,
+            metallic,
+            roughness,
+            specular: self.specular,
+        }
+    }
+}
+
+///
+/// Material properties at a point of a surface, once textures are evaluated.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Surface {
+    /// Base color: diffuse albedo of dielectrics, and specular reflectance of metals.
+    pub color: Color,
+    /// Light emitted by the surface.
+    pub emission: Color,
+    /// Metalness, from `0` (dielectric) to `1` (metal).
+    pub metallic: f32,
+    /// Perceptual roughness, from `0` (mirror-like) to `1` (fully rough).
+    pub roughness: f32,
+    /// Strength of the specular reflection of dielectrics, from `0` (Lambertian) to `1`.
+    pub specular: f32,
+}
+
+impl Surface {
+    ///
+    /// Evaluate the BRDF, for light coming from `wi` and reflected toward `wo`, on a surface of
+    /// normal `n`.
+    ///
+    /// All vectors are unit vectors pointing away from the surface.
+    ///
+    pub fn brdf(&self, n: Vector, wo: Vector, wi: Vector) -> Color {
+        let (cos_o, cos_i) = (n.dot(wo), n.dot(wi));
+
+        if cos_o <= 0. || cos_i <= 0. {
+            return Color::BLACK;
+        }
+
+        let h = (wo + wi).normalize();
+        let alpha = self.alpha();
+        let fresnel = self.fresnel(wo.dot(h));
+
+        let spec = microfacet::ggx(n.dot(h), alpha)
+            * microfacet::smith_g1(cos_o, alpha)
+            * microfacet::smith_g1(cos_i, alpha)
+            / (4. * cos_o * cos_i);
+
+        // The diffuse lobe only gets the light which isn't specularly reflected, either on its
+        // way in or out, which keeps the sum of both lobes from exceeding 1 at grazing angles.
+        let (fresnel_o, fresnel_i) = (self.fresnel(cos_o), self.fresnel(cos_i));
+        let diffuse =
+            |fo: f32, fi: f32, c: f32| (1. - fo) * (1. - fi) * (1. - self.metallic) * c * FRAC_1_PI;
+
+        Color::from_rgb(
+            diffuse(fresnel_o.r, fresnel_i.r, self.color.r) + fresnel.r * spec,
+            diffuse(fresnel_o.g, fresnel_i.g, self.color.g) + fresnel.g * spec,
+            diffuse(fresnel_o.b, fresnel_i.b, self.color.b) + fresnel.b * spec,
+        )
+    }
+
+    ///
+    /// Return the probability density of [sample](Self::sample) drawing `wi`, given `wo`.
+    ///
+    pub fn pdf(&self, n: Vector, wo: Vector, wi: Vector) -> f32 {
+        let cos_i = n.dot(wi);
+
+        if n.dot(wo) <= 0. || cos_i <= 0. {
+            return 0.;
+        }
+
+        let h = (wo + wi).normalize();
+        let cos_h = n.dot(h);
+        let spec_pdf = microfacet::ggx(cos_h, self.alpha()) * cos_h / (4. * wo.dot(h).abs());
+        let diffuse_pdf = cos_i * FRAC_1_PI;
+        let p = self.specular_probability(n.dot(wo));
+
+        p * spec_pdf + (1. - p) * diffuse_pdf
+    }
+
+    ///
+    /// Draw a direction `wi` of incoming light, given the outgoing direction `wo`, on a surface
+    /// of normal `n`.
+    ///
+    /// Directions are drawn following either the diffuse or the specular lobe, so that the
+    /// returned directions roughly follow the BRDF. `u` is a pair of uniform random numbers in
+    /// `[0, 1)`.
+    ///
+    /// Return the drawn direction, and the BRDF weighted by the cosine term and divided by the
+    /// probability density of the direction.
+    ///
+    pub fn sample(&self, n: Vector, wo: Vector, (u, v): (f32, f32)) -> Option<(Vector, Color)> {
+        let p = self.specular_probability(n.dot(wo));
+
+        let wi = if u < p {
+            let h = microfacet::sample_ggx(n, self.alpha(), (u / p, v));
+            h * (2. * wo.dot(h)) - wo
+        } else {
+            sampling::cosine_hemisphere(n, ((u - p) / (1. - p), v))
+        };
+
+        let pdf = self.pdf(n, wo, wi);
+        if pdf <= 0. {
+            return None;
+        }
+
+        Some((wi, self.brdf(n, wo, wi) * (n.dot(wi) / pdf)))
+    }
+
+    ///
+    /// Microfacet roughness, squared from the perceptual roughness.
+    ///
+    fn alpha(&self) -> f32 {
+        (self.roughness * self.roughness).max(1e-3)
+    }
+
+    ///
+    /// Fresnel reflectance, for a cosine `cos` of the angle between light and microfacet normal.
+    ///
+    /// The Schlick approximation being affine in the reflectance at normal incidence, blending
+    /// the dielectric and metal reflectances is the same as blending their Fresnel terms.
+    ///
+    pub fn fresnel(&self, cos: f32) -> Color {
+        let dielectric = self.specular * microfacet::schlick(DIELECTRIC_F0, cos);
+        let f = |c: f32| dielectric + (microfacet::schlick(c, cos) - dielectric) * self.metallic;
+
+        Color::from_rgb(f(self.color.r), f(self.color.g), f(self.color.b))
+    }
+
+    ///
+    /// Probability of sampling the specular lobe rather than the diffuse one, for a cosine
+    /// `cos_o` of the angle between the outgoing direction and the normal.
+    ///
+    /// Lobes are picked in proportion to their estimated reflectance, so that Lambertian surfaces
+    /// never sample the specular lobe.
+    ///
+    fn specular_probability(&self, cos_o: f32) -> f32 {
+        let mean = |c: Color| (c.r + c.g + c.b) / 3.;
+        let spec = mean(self.fresnel(cos_o));
+        let diffuse = (1. - spec) * (1. - self.metallic) * mean(self.color);
+
+        if spec + diffuse > 0. {
+            (spec / (spec + diffuse)).clamp(0., 1.)
+        } else {
+            0.
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nannou::rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        maths::Point,
+        scene::texture::{ImageTexture, Wrap},
+    };
+
+    fn surface(material: Material) -> Surface {
+        material.surface(&Intersection::new(1., Point::ORIGIN, Vector::Z))
+    }
+
+    fn surfaces() -> [Surface; 5] {
+        let white = Color::WHITE;
+
+        [
+            surface(Material::DEFAULT),
+            surface(Material::pbr(white, 0., 0.8)),
+            surface(Material::pbr(white, 0., 0.2)),
+            surface(Material::pbr(white, 1., 0.5)),
+            surface(Material::pbr(white, 0.5, 0.3)),
+        ]
+    }
+
+    fn outgoing() -> [Vector; 3] {
+        [
+            Vector::Z,
+            Vector::new(0.5, 0., 1.).normalize(),
+            Vector::new(-1., 2., 0.5).normalize(),
+        ]
+    }
+
+    #[test]
+    fn lambertian_test() {
+        let grey = Color::new(0.5, 0.5, 0.5, 1.);
+        let s = surface(Material::diffuse(grey));
+        let (n, wo) = (Vector::Z, Vector::new(1., 0., 1.).normalize());
+        let wi = Vector::new(-0.2, 0.6, 0.4).normalize();
+
+        assert_eq!(Material::DEFAULT.specular, 0.);
+        let brdf = s.brdf(n, wo, wi);
+        assert!((brdf.r - 0.5 * FRAC_1_PI).abs() <= 1e-6 && brdf.r == brdf.b);
+        assert!((s.pdf(n, wo, wi) - wi.z * FRAC_1_PI).abs() <= 1e-6);
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        for _ in 0..64 {
+            let (_, weight) = s.sample(n, wo, rng.gen()).unwrap();
+            assert!((weight.r - 0.5).abs() <= 1e-5 && (weight.b - 0.5).abs() <= 1e-5);
+        }
+    }
+
+    #[test]
+    fn sample_consistency_test() {
+        let n = Vector::Z;
+        let mut rng = SmallRng::seed_from_u64(2);
+
+        for s in surfaces() {
+            for wo in outgoing() {
+                for _ in 0..256 {
+                    let Some((wi, weight)) = s.sample(n, wo, rng.gen()) else {
+                        continue
+                    };
+                    let pdf = s.pdf(n, wo, wi);
+                    let expected = s.brdf(n, wo, wi) * (n.dot(wi) / pdf);
+
+                    assert!(pdf > 0.);
+                    assert!((weight.r - expected.r).abs() <= 1e-3 * expected.r.max(1.));
+                    assert!((weight.g - expected.g).abs() <= 1e-3 * expected.g.max(1.));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pdf_integral_test() {
+        // Integrate the pdf over the hemisphere, by uniform sampling.
+        let n = Vector::Z;
+        let mut rng = SmallRng::seed_from_u64(3);
+        let count = 20000;
+
+        for s in surfaces().into_iter().filter(|s| s.roughness > 0.4) {
+            for wo in outgoing() {
+                let integral = (0..count)
+                    .map(|_| {
+                        let wi = sampling::uniform_sphere(rng.gen());
+                        let wi = if wi.dot(n) < 0. { -wi } else { wi };
+                        s.pdf(n, wo, wi) * 2. * std::f32::consts::PI
+                    })
+                    .sum::<f32>()
+                    / count as f32;
+
+                // Specular samples reflected below the surface are lost, so the integral may be
+                // lower than 1.
+                assert!(integral <= 1.05, "{integral}");
+                if s.specular == 0. {
+                    assert!((integral - 1.).abs() <= 0.02, "{integral}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn furnace_test() {
+        // A white surface can't reflect more light than it receives.
+        let n = Vector::Z;
+        let mut rng = SmallRng::seed_from_u64(4);
+        let count = 20000;
+
+        for s in surfaces() {
+            for wo in outgoing() {
+                let albedo = (0..count)
+                    .filter_map(|_| s.sample(n, wo, rng.gen()))
+                    .fold(0., |acc, (_, w)| acc + w.g)
+                    / count as f32;
+
+                assert!(albedo <= 1.02, "{albedo}");
+            }
+        }
+
+        let albedo = (0..count)
+            .filter_map(|_| surfaces()[0].sample(n, Vector::Z, rng.gen()))
+            .fold(0., |acc, (_, w)| acc + w.g)
+            / count as f32;
+        assert!((albedo - 1.).abs() <= 1e-4);
+    }
+
+    #[test]
+    fn normal_map_test() {
+        let inter = Intersection::new(1., Point::ORIGIN, Vector::Z).with_uv(
+            (0.5, 0.5),
+            Vector::X,
+            Vector::Y,
+        );
+
+        // A flat normal map leaves the normal unchanged.
+        let flat = Material::DEFAULT.with_normal_map(Color::from_rgb(0.5, 0.5, 1.));
+        assert!((flat.perturb(inter).normal - Vector::Z).magn() <= 1e-6);
+
+        // Channels are mapped to the tangent frame.
+        let tilted = Material::DEFAULT.with_normal_map(Color::from_rgb(1., 0.5, 1.));
+        let normal = tilted.perturb(inter).normal;
+        assert!((normal - Vector::new(1., 0., 1.).normalize()).magn() <= 1e-6);
+        assert_eq!(tilted.perturb(inter).geometric_normal, Vector::Z);
+    }
+
+    #[test]
+    fn bump_test() {
+        let inter = Intersection::new(1., Point::ORIGIN, Vector::Z).with_uv(
+            (0.5, 0.5),
+            Vector::X,
+            Vector::Y,
+        );
+
+        // A flat bump map leaves the normal unchanged.
+        let flat = Material::DEFAULT.with_bump_map(Color::from_rgb(0.3, 0.3, 0.3), 2.);
+        assert!((flat.perturb(inter).normal - Vector::Z).magn() <= 1e-6);
+
+        // Heights growing along `u` tilt the normal backward: between the texel centers, the
+        // height is `scale × (2u - 0.5)`.
+        let slope =
+            ImageTexture::new(2, 1, vec![Color::BLACK, Color::WHITE]).with_wrap(Wrap::Clamp);
+        let bumped = Material::DEFAULT.with_bump_map(slope, 0.5);
+        let normal = bumped.perturb(inter).normal;
+        assert!((normal - Vector::new(-1., 0., 1.).normalize()).magn() <= 1e-3);
+
+        // Without derivatives, surfaces can't be bump mapped.
+        let inter = Intersection::new(1., Point::ORIGIN, Vector::Z);
+        assert_eq!(bumped.perturb(inter).normal, Vector::Z);
+    }
+}
@@ -4,23 +4,41 @@
 //! [Objects](Object) are rendering primitives.
 //!
 
-use crate::render::{Intersection, Ray};
+use std::f32::consts::PI;
 
+use crate::{
+    maths::{Point, Vector},
+    render::{Intersection, Ray},
+};
+
+pub use super::material::Material;
+
+pub mod bezier;
+pub mod blob;
 pub mod cone;
+pub mod curves;
 pub mod cylinder;
+pub mod heightfield;
 pub mod plan;
+pub mod quadric;
 pub mod sphere;
 
+pub use bezier::BezierPatch;
+pub use blob::{Blob, BlobSource};
 pub use cone::Cone;
+pub use curves::{CurveKind, CurveSegment, Curves};
 pub use cylinder::Cylinder;
+pub use heightfield::Heightfield;
 pub use plan::Plan;
+pub use quadric::Quadric;
 pub use sphere::Sphere;
 
 ///
 /// Trait representing 3D objects.
 ///
-pub trait Object {
+pub trait Object: Send + Sync {
     fn intersect(&self, ray: Ray) -> Option<Intersection>;
+    fn material(&self) -> &Material;
     fn cloned(&self) -> Box<dyn Object>;
 }
 
@@ -29,3 +47,69 @@ impl Clone for Box<dyn Object> {
         self.cloned()
     }
 }
+
+///
+/// Cylindrical texture mapping of the point `pos`, around the axis going through `origin` in the
+/// unit direction `axis`.
+///
+/// `u` goes around the axis, from `0` to `1`, and `v` is the signed distance from `origin` along
+/// the axis. Return the texture coordinates, and the partial derivative of `pos` with respect to
+/// `u`.
+///
+fn cylindrical_mapping(pos: Point, origin: Point, axis: Vector) -> ((f32, f32), Vector) {
+    let (b, t) = axis.any_orthonormal_pair();
+    let local = pos - origin;
+    let (x, y) = (local.dot(t), local.dot(b));
+    let phi = y.atan2(x);
+
+    let u = 0.5 + phi / (2. * PI);
+    let v = local.dot(axis);
+
+    // Moving along `u` follows a circle of radius the distance to the axis.
+    ((u, v), (b * x - t * y) * (2. * PI))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Check that moving from the hit point of `ray` along `dpdu` and `dpdv` changes the texture
+    /// coordinates accordingly, by shooting rays slightly aside.
+    ///
+    pub(super) fn assert_uv_derivatives(object: &impl Object, ray: Ray) {
+        let inter = object.intersect(ray).unwrap();
+        let eps = 1e-4;
+
+        for (d, (du, dv)) in [(inter.dpdu, (eps, 0.)), (inter.dpdv, (0., eps))] {
+            let target = inter.pos + d * eps;
+            let aside = object
+                .intersect(Ray::new(ray.orig, (target - ray.orig).normalize()))
+                .unwrap();
+
+            assert!((aside.uv.0 - inter.uv.0 - du).abs() <= 0.05 * eps);
+            assert!((aside.uv.1 - inter.uv.1 - dv).abs() <= 0.05 * eps);
+        }
+    }
+
+    #[test]
+    fn cylindrical_mapping_test() {
+        let axis = Vector::new(1., 2., -1.).normalize();
+        let origin = Point::new(1., 0., 3.);
+        let (b, t) = axis.any_orthonormal_pair();
+
+        // Points along `t` are at the middle of the texture, and `u` grows toward `b`.
+        let ((u, v), dpdu) = cylindrical_mapping(origin + t * 2. + axis * 1.5, origin, axis);
+        assert!((u - 0.5).abs() <= 1e-6 && (v - 1.5).abs() <= 1e-5);
+        assert!((dpdu - b * (4. * PI)).magn() <= 1e-4);
+
+        let ((u, v), dpdu) = cylindrical_mapping(origin + b * 2. - axis, origin, axis);
+        assert!((u - 0.75).abs() <= 1e-6 && (v + 1.).abs() <= 1e-5);
+        assert!((dpdu + t * (4. * PI)).magn() <= 1e-4);
+
+        // Opposite points are half a turn apart.
+        let ((u1, _), _) = cylindrical_mapping(origin + t + b * 0.5, origin, axis);
+        let ((u2, _), _) = cylindrical_mapping(origin - t - b * 0.5, origin, axis);
+        assert!(((u1 - u2).abs() - 0.5).abs() <= 1e-6);
+    }
+}
@@ -0,0 +1,371 @@
+//!
+//! Bicubic Bézier patch.
+//!
+//! A bicubic Bézier patch is a smooth surface, defined by a 4x4 grid of control points. Any point
+//! of the surface is a weighted sum of the control points:
+//! ```text
+//! S(u, v) = sum(i, j) Bi(v) * Bj(u) * P[i][j]
+//! ```
+//! with `Bk` being the cubic Bernstein polynomials, and `(u, v)` the surface parametric
+//! coordinates, in `[0, 1]`.
+//!
+//! Patches are intersected directly, without tessellation: the patch is split into sub-patches
+//! whose bounding boxes are tested first, then the exact intersection is refined by Newton
+//! iterations. This gives exact silhouettes and continuous normals, which is what makes the
+//! Utah teapot or CAD surfaces look smooth.
+//!
+
+use std::sync::Arc;
+
+use crate::{
+    maths::{Aabb, Point, Vector},
+    render::{Intersection, Ray},
+};
+
+use super::{Material, Object};
+
+/// Number of sub-patches along each parametric direction.
+const SPLITS: usize = 8;
+/// Maximum number of Newton iterations per sub-patch.
+const NEWTON_STEPS: usize = 12;
+
+///
+/// Bicubic Bézier patch.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone)]
+pub struct BezierPatch {
+    /// Control points, as vectors from the origin. Stored row by row, rows being along `u`.
+    points: [Vector; 16],
+    /// Sub-patches bounding boxes, with their `(u, v)` parametric ranges.
+    cells: Arc<[(Aabb, [f32; 4])]>,
+    bounds: Aabb,
+    material: Material,
+}
+
+impl BezierPatch {
+    ///
+    /// Create a new patch from its 16 control points.
+    ///
+    /// Points are given row by row: `points[4 * i + j]` is `P[i][j]`, `j` going along `u`, and
+    /// `i` along `v`.
+    ///
+    pub fn new(points: [Point; 16]) -> Self {
+        let points = points.map(|p| p - Point::ORIGIN);
+
+        let cells: Vec<_> = (0..SPLITS)
+            .flat_map(|i| (0..SPLITS).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                let step = 1. / SPLITS as f32;
+                let range = [
+                    j as f32 * step,
+                    (j + 1) as f32 * step,
+                    i as f32 * step,
+                    (i + 1) as f32 * step,
+                ];
+
+                (Self::sub_hull(&points, range), range)
+            })
+            .collect();
+
+        let bounds = cells
+            .iter()
+            .fold(Aabb::EMPTY, |acc, (cell, _)| acc.union(*cell));
+
+        Self {
+            points,
+            cells: cells.into(),
+            bounds,
+            material: Material::DEFAULT,
+        }
+    }
+
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    ///
+    /// Compute the bounding box of the sub-patch spanning `[u0, u1, v0, v1]`.
+    ///
+    /// The control points of the sub-patch are computed exactly, and as a Bézier patch lies in
+    /// the convex hull of its control points, their bounding box also bounds the sub-patch.
+    ///
+    fn sub_hull(points: &[Vector; 16], [u0, u1, v0, v1]: [f32; 4]) -> Aabb {
+        let mut rows = [[Vector::ZERO; 4]; 4];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            let curve = [0, 1, 2, 3].map(|j| points[4 * i + j]);
+            *row = restrict(curve, u0, u1);
+        }
+
+        let hull = (0..4).flat_map(|j| restrict([0, 1, 2, 3].map(|i| rows[i][j]), v0, v1));
+
+        Aabb::from_points(hull.map(|v| Point::ORIGIN + v))
+    }
+
+    ///
+    /// Evaluate the surface position, and its partial derivatives along `u` and `v`.
+    ///
+    pub fn eval(&self, u: f32, v: f32) -> (Point, Vector, Vector) {
+        let (bu, du) = (bernstein(u), bernstein_derivative(u));
+        let (bv, dv) = (bernstein(v), bernstein_derivative(v));
+
+        let mut pos = Vector::ZERO;
+        let mut su = Vector::ZERO;
+        let mut sv = Vector::ZERO;
+
+        for i in 0..4 {
+            for j in 0..4 {
+                let p = self.points[4 * i + j];
+
+                pos += p * (bv[i] * bu[j]);
+                su += p * (bv[i] * du[j]);
+                sv += p * (dv[i] * bu[j]);
+            }
+        }
+
+        (Point::ORIGIN + pos, su, sv)
+    }
+
+    ///
+    /// Compute the surface normal at `(u, v)`.
+    ///
+    /// Degenerated patches (such as the top of the Utah teapot) collapse a whole edge into a
+    /// single point, where the derivatives cross product vanishes. In that case, the normal is
+    /// taken slightly towards the patch center.
+    ///
+    fn normal(&self, u: f32, v: f32) -> Vector {
+        let (_, su, sv) = self.eval(u, v);
+        let n = su.cross(sv);
+
+        if n.magn2() > 1e-12 {
+            n
+        } else {
+            let (_, su, sv) = self.eval(u + (0.5 - u) * 1e-3, v + (0.5 - v) * 1e-3);
+            su.cross(sv)
+        }
+    }
+
+    ///
+    /// Refine an intersection by Newton iterations, starting from `(u, v, t)`.
+    ///
+    /// Each step solves the linear system `Su * du + Sv * dv - dir * dt = -F`, with
+    /// `F = S(u, v) - (orig + t * dir)`, using Cramer's rule.
+    ///
+    fn newton(&self, ray: Ray, mut u: f32, mut v: f32, mut t: f32) -> Option<(f32, f32, f32)> {
+        for _ in 0..NEWTON_STEPS {
+            let (pos, su, sv) = self.eval(u, v);
+            let f = pos - (ray.orig + ray.dir * t);
+
+            if f.magn2() < 1e-10 {
+                return Some((u, v, t));
+            }
+
+            let d = -ray.dir;
+            let det = su.dot(sv.cross(d));
+
+            if det.abs() < 1e-12 {
+                return None;
+            }
+
+            let rhs = -f;
+            u += rhs.dot(sv.cross(d)) / det;
+            v += su.dot(rhs.cross(d)) / det;
+            t += su.dot(sv.cross(rhs)) / det;
+        }
+
+        let (pos, _, _) = self.eval(u, v);
+        ((pos - (ray.orig + ray.dir * t)).magn2() < 1e-8).then_some((u, v, t))
+    }
+
+    ///
+    /// Compute the closest intersection between a `Ray` and the patch, along with its parametric
+    /// coordinates `(u, v)`.
+    ///
+    /// Sub-patches are tested in order of their bounding box entry distance, and Newton
+    /// iterations are started from the center of each hit sub-patch. A solution is only kept if
+    /// it lies inside the sub-patch it was started from, which prevents the iterations from
+    /// converging to a farther part of the surface.
+    ///
+    pub fn intersect_uv(&self, ray: Ray) -> Option<(Intersection, (f32, f32))> {
+        self.bounds.intersect(ray)?;
+
+        let mut hits: Vec<_> = self
+            .cells
+            .iter()
+            .filter_map(|(cell, range)| cell.intersect(ray).map(|(near, _)| (near, range)))
+            .collect();
+        hits.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+
+        let mut best: Option<(f32, f32, f32)> = None;
+
+        for (near, [u0, u1, v0, v1]) in hits {
+            if best.is_some_and(|(_, _, t)| t < near) {
+                break;
+            }
+
+            let eps = 1e-4;
+            let found = self
+                .newton(ray, (u0 + u1) / 2., (v0 + v1) / 2., near.max(0.))
+                .filter(|(u, v, t)| {
+                    *t > 1e-4
+                        && (u0 - eps..=u1 + eps).contains(u)
+                        && (v0 - eps..=v1 + eps).contains(v)
+                });
+
+            if let Some(hit) = found {
+                if best.is_none_or(|(_, _, t)| hit.2 < t) {
+                    best = Some(hit);
+                }
+            }
+        }
+
+        best.map(|(u, v, t)| {
+            let (u, v) = (u.clamp(0., 1.), v.clamp(0., 1.));
+            let (_, su, sv) = self.eval(u, v);
+            let inter = Intersection::new(t, ray.orig + ray.dir * t, self.normal(u, v)).with_uv(
+                (u, v),
+                su,
+                sv,
+            );
+
+            (inter, (u, v))
+        })
+    }
+
+    pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        self.intersect_uv(ray).map(|(inter, _)| inter)
+    }
+}
+
+///
+/// Cubic Bernstein polynomials, evaluated at `t`.
+///
+fn bernstein(t: f32) -> [f32; 4] {
+    let s = 1. - t;
+
+    [s * s * s, 3. * t * s * s, 3. * t * t * s, t * t * t]
+}
+
+///
+/// Derivatives of the cubic Bernstein polynomials, evaluated at `t`.
+///
+fn bernstein_derivative(t: f32) -> [f32; 4] {
+    let s = 1. - t;
+
+    [
+        -3. * s * s,
+        3. * s * (s - 2. * t),
+        3. * t * (2. * s - t),
+        3. * t * t,
+    ]
+}
+
+///
+/// Compute the control points of a cubic Bézier curve, restricted to `[a, b]`.
+///
+/// A cubic is fully defined by its end points and tangents, which gives the new control points
+/// as `B(a)`, `B(a) + (b - a) / 3 * B'(a)`, `B(b) - (b - a) / 3 * B'(b)` and `B(b)`.
+///
+fn restrict(curve: [Vector; 4], a: f32, b: f32) -> [Vector; 4] {
+    let combine = |basis: [f32; 4]| {
+        curve
+            .iter()
+            .zip(basis)
+            .fold(Vector::ZERO, |acc, (p, w)| acc + *p * w)
+    };
+
+    let (pa, da) = (combine(bernstein(a)), combine(bernstein_derivative(a)));
+    let (pb, db) = (combine(bernstein(b)), combine(bernstein_derivative(b)));
+    let k = (b - a) / 3.;
+
+    [pa, pa + da * k, pb - db * k, pb]
+}
+
+impl Object for BezierPatch {
+    fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        self.intersect(ray)
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn cloned(&self) -> Box<dyn Object> {
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Patch spanning `[0, 3] x [0, 3]` on the `z = 5` plane, with the 4 inner control points
+    /// moved to `z = inner`.
+    ///
+    fn patch(inner: f32) -> BezierPatch {
+        BezierPatch::new(std::array::from_fn(|k| {
+            let (i, j) = (k / 4, k % 4);
+            let z = if (1..3).contains(&i) && (1..3).contains(&j) {
+                inner
+            } else {
+                5.
+            };
+
+            Point::new(j as f32, i as f32, z)
+        }))
+    }
+
+    #[test]
+    fn flat_hit_test() {
+        let (inter, (u, v)) = patch(5.)
+            .intersect_uv(Ray::new(Point::new(0.75, 2.25, 0.), Vector::Z))
+            .unwrap();
+
+        assert!((inter.dist - 5.).abs() <= 1e-4);
+        assert!((u - 0.25).abs() <= 1e-4 && (v - 0.75).abs() <= 1e-4);
+        // su = X and sv = Y, so the normal is along +Z.
+        assert!((inter.normal - Vector::Z).magn() <= 1e-4);
+    }
+
+    #[test]
+    fn miss_test() {
+        let p = patch(4.);
+
+        assert!(p
+            .intersect(Ray::new(Point::new(3.5, 1., 0.), Vector::Z))
+            .is_none());
+        assert!(p
+            .intersect(Ray::new(Point::new(1., 1., 0.), -Vector::Z))
+            .is_none());
+    }
+
+    #[test]
+    fn curved_hit_test() {
+        let p = patch(4.);
+        let (inter, (u, v)) = p
+            .intersect_uv(Ray::new(Point::new(1.5, 1.5, 0.), Vector::Z))
+            .unwrap();
+
+        // The inner points weigh (3/8 + 3/8)^2 at the center of the patch.
+        assert!((inter.dist - 4.4375).abs() <= 1e-3);
+        assert!((u - 0.5).abs() <= 1e-3 && (v - 0.5).abs() <= 1e-3);
+        assert!((inter.normal - Vector::Z).magn() <= 1e-3);
+
+        // Off center, the hit point lies on the surface, and the normal is orthogonal to it.
+        let (inter, (u, v)) = p
+            .intersect_uv(Ray::new(Point::new(0.7, 2.1, 0.), Vector::Z))
+            .unwrap();
+        let (pos, su, sv) = p.eval(u, v);
+
+        assert!((pos - inter.pos).magn() <= 1e-3);
+        assert!(inter.normal.dot(su.normalize()).abs() <= 1e-3);
+        assert!(inter.normal.dot(sv.normalize()).abs() <= 1e-3);
+    }
+}
@@ -0,0 +1,250 @@
+//!
+//! Implicit blob (metaballs).
+//!
+//! A blob is the iso-surface of a scalar field, summed from a set of spherical sources. Each
+//! source has a position, a radius of influence `R` and a strength `s`, and contributes to the
+//! field at a point `p` as:
+//! ```text
+//! f(p) = s * (1 - r^2 / R^2)^2    if r < R
+//! f(p) = 0                        otherwise
+//! ```
+//! with `r` being the distance between `p` and the source.
+//!
+//! The blob surface is made of the points where the field equals the blob threshold. Sources
+//! close to each other melt together, and sources with a negative strength carve holes.
+//!
+
+use std::sync::Arc;
+
+use crate::{
+    maths::{Point, Polynom, Polynom2, Vector},
+    render::{Intersection, Ray},
+};
+
+use super::{Material, Object};
+
+///
+/// Spherical field source of a [Blob].
+///
+#[derive(Debug, Clone, Copy)]
+pub struct BlobSource {
+    pos: Point,
+    radius: f32,
+    strength: f32,
+}
+
+impl BlobSource {
+    pub fn new(pos: Point, radius: f32, strength: f32) -> Self {
+        Self {
+            pos,
+            radius,
+            strength,
+        }
+    }
+
+    fn field(&self, p: Point) -> f32 {
+        let w = 1. - (p - self.pos).magn2() / self.radius.powi(2);
+
+        if w > 0. {
+            self.strength * w.powi(2)
+        } else {
+            0.
+        }
+    }
+
+    fn gradient(&self, p: Point) -> Vector {
+        let adj = p - self.pos;
+        let w = 1. - adj.magn2() / self.radius.powi(2);
+
+        if w > 0. {
+            adj * (-4. * self.strength * w / self.radius.powi(2))
+        } else {
+            Vector::ZERO
+        }
+    }
+}
+
+///
+/// Implicit blob object.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone)]
+pub struct Blob {
+    sources: Arc<[BlobSource]>,
+    threshold: f32,
+    material: Material,
+}
+
+impl Blob {
+    ///
+    /// Create a new blob.
+    ///
+    /// `threshold` is the field value at the surface, it should be lower than the sources
+    /// strength for them to be visible.
+    ///
+    pub fn new(sources: Vec<BlobSource>, threshold: f32) -> Self {
+        Self {
+            sources: sources.into(),
+            threshold,
+            material: Material::DEFAULT,
+        }
+    }
+
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    ///
+    /// Compute the blob field at `p`.
+    ///
+    pub fn field(&self, p: Point) -> f32 {
+        self.sources.iter().map(|s| s.field(p)).sum()
+    }
+
+    ///
+    /// Compute the closest intersection between a `Ray` and the blob.
+    ///
+    /// Along the ray, the squared distance to a source is a 2nd degree polynomial in `k`, the
+    /// distance along the ray. Each source field is thus a 4th degree polynomial, inside an
+    /// interval bounded by the intersections of the ray and the source sphere of influence.
+    ///
+    /// The interval bounds split the ray into segments where the set of active sources doesn't
+    /// change. Segments are walked in order, and on each of them the summed field minus the
+    /// threshold is a single polynomial, whose first root is the closest intersection.
+    ///
+    /// The normal is the opposite of the field gradient, which is computed analytically.
+    ///
+    pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        let active: Vec<_> = self
+            .sources
+            .iter()
+            .filter_map(|s| {
+                let adj = ray.orig - s.pos;
+                let r2 = s.radius.powi(2);
+
+                let [t0, t1] = Polynom2::new(1., 2. * adj.dot(ray.dir), adj.magn2() - r2)
+                    .roots()
+                    .filter(|[_, t1]| !t1.is_nan() && *t1 > 0.)?;
+
+                // w(k) = 1 - (k^2 + 2k * adj.dir + ||adj||^2) / R^2
+                let w = Polynom::new(vec![
+                    1. - adj.magn2() / r2,
+                    -2. * adj.dot(ray.dir) / r2,
+                    -1. / r2,
+                ]);
+
+                Some((t0.max(0.), t1, w.clone() * w * s.strength))
+            })
+            .collect();
+
+        let mut bounds: Vec<f32> = active.iter().flat_map(|(t0, t1, _)| [*t0, *t1]).collect();
+        bounds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        bounds.dedup();
+
+        let dist = bounds.windows(2).find_map(|w| {
+            let (lo, hi) = (w[0].max(1e-4), w[1]);
+            if lo >= hi {
+                return None;
+            }
+            let mid = (lo + hi) / 2.;
+
+            let field = active
+                .iter()
+                .filter(|(t0, t1, _)| (*t0..=*t1).contains(&mid))
+                .fold(Polynom::new(vec![-self.threshold]), |acc, (_, _, p)| {
+                    acc + p.clone()
+                });
+
+            field.first_root_in(lo, hi)
+        })?;
+
+        let pos = ray.orig + ray.dir * dist;
+        let gradient = self
+            .sources
+            .iter()
+            .fold(Vector::ZERO, |acc, s| acc + s.gradient(pos));
+
+        Some(Intersection::new(dist, pos, -gradient))
+    }
+}
+
+impl Object for Blob {
+    fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        self.intersect(ray)
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn cloned(&self) -> Box<dyn Object> {
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_source_test() {
+        // (1 - r^2 / 4)^2 = 0.25 gives a sphere of radius sqrt(2).
+        let b = Blob::new(vec![BlobSource::new(Point::new(0., 0., 5.), 2., 1.)], 0.25);
+        let inter = b.intersect(Ray::new(Point::ORIGIN, Vector::Z)).unwrap();
+
+        assert!((inter.dist - (5. - 2f32.sqrt())).abs() <= 1e-3);
+        assert!((inter.normal - -Vector::Z).magn() <= 1e-3);
+        assert!((b.field(inter.pos) - 0.25).abs() <= 1e-3);
+    }
+
+    #[test]
+    fn miss_test() {
+        let b = Blob::new(vec![BlobSource::new(Point::new(0., 0., 5.), 2., 1.)], 0.25);
+
+        assert!(b
+            .intersect(Ray::new(Point::new(1.5, 0., 0.), Vector::Z))
+            .is_none());
+        assert!(b.intersect(Ray::new(Point::ORIGIN, -Vector::Z)).is_none());
+    }
+
+    #[test]
+    fn merged_sources_test() {
+        let b = Blob::new(
+            vec![
+                BlobSource::new(Point::new(-1., 0., 5.), 2., 1.),
+                BlobSource::new(Point::new(1., 0., 5.), 2., 1.),
+            ],
+            0.25,
+        );
+
+        // The middle of the two sources is inside the surface.
+        assert!(b.field(Point::new(0., 0., 5.)) > 0.25);
+
+        let inter = b
+            .intersect(Ray::new(Point::new(0., 5., 5.), -Vector::Y))
+            .unwrap();
+        assert!((inter.normal - Vector::Y).magn() <= 1e-3);
+        assert!((b.field(inter.pos) - 0.25).abs() <= 1e-3);
+    }
+
+    #[test]
+    fn negative_source_test() {
+        let b = Blob::new(
+            vec![
+                BlobSource::new(Point::new(0., 0., 5.), 2., 1.),
+                BlobSource::new(Point::new(0., 0., 3.5), 1., -1.),
+            ],
+            0.25,
+        );
+        let inter = b.intersect(Ray::new(Point::ORIGIN, Vector::Z)).unwrap();
+
+        // The negative source carves a hole in front of the sphere.
+        assert!(inter.dist > 5. - 2f32.sqrt() + 0.1);
+        assert!((b.field(inter.pos) - 0.25).abs() <= 1e-3);
+    }
+}
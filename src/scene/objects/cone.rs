@@ -7,16 +7,17 @@ use crate::{
     render::{Intersection, Ray},
 };
 
-use super::Object;
+use super::{Material, Object};
 
 ///
 /// Infinite Cone
 ///
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Cone {
     pos: Point,
     dir: Vector,
     angle: f32,
+    material: Material,
 }
 
 impl Cone {
@@ -25,9 +26,24 @@ impl Cone {
             pos,
             dir: dir.normalize(),
             angle: angle.to_radians(),
+            material: Material::DEFAULT,
         }
     }
 
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    ///
+    /// Compute the intersection between a [Ray] and the cone.
+    ///
+    /// Texture coordinates use a cylindrical mapping: `u` goes around the axis, and `v` is the
+    /// distance along the axis from the apex.
+    ///
     pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
         let adj = ray.orig - self.pos;
 
@@ -48,7 +64,17 @@ impl Cone {
 
             let projection = self.pos + self.dir * (v.magn2() / self.dir.dot(v));
 
-            Some(Intersection::new(dist, point, point - projection))
+            let (uv, dpdu) = super::cylindrical_mapping(point, self.pos, self.dir);
+            // Moving along `v` follows the line going through the apex, which is degenerate at the
+            // apex itself.
+            let height = self.dir.dot(v);
+            let dpdv = if height.abs() > f32::EPSILON {
+                v / height
+            } else {
+                Vector::ZERO
+            };
+
+            Some(Intersection::new(dist, point, point - projection).with_uv(uv, dpdu, dpdv))
         } else {
             None
         }
@@ -60,7 +86,45 @@ impl Object for Cone {
         self.intersect(ray)
     }
 
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
     fn cloned(&self) -> Box<dyn Object> {
-        Box::new(*self) as Box<dyn Object>
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::scene::objects::tests::assert_uv_derivatives;
+
+    #[test]
+    fn uv_test() {
+        // A 90° cone, opening upward from its apex at the origin.
+        let cone = Cone::new(Point::ORIGIN, Vector::Y, 90.);
+        let (b, t) = Vector::Y.any_orthonormal_pair();
+
+        // At height 2, the radius of the cone is 2 as well.
+        let orig = Point::new(0., 2., 0.) + t * 10.;
+        let inter = cone.intersect(Ray::new(orig, -t)).unwrap();
+
+        assert!((inter.pos - (Point::new(0., 2., 0.) + t * 2.)).magn() <= 1e-4);
+        assert!((inter.uv.0 - 0.5).abs() <= 1e-6 && (inter.uv.1 - 2.).abs() <= 1e-5);
+        assert!((inter.dpdu - b * (4. * PI)).magn() <= 1e-3);
+        // Moving along `v` goes up the cone, away from the apex.
+        assert!((inter.dpdv - (Vector::Y + t)).magn() <= 1e-5);
+    }
+
+    #[test]
+    fn uv_derivatives_test() {
+        let cone = Cone::new(Point::new(0., -3., 5.), Vector::new(0.1, 1., 0.), 60.);
+
+        for dir in [Vector::new(0.2, 0.1, 1.), Vector::new(-0.3, 0.2, 1.)] {
+            assert_uv_derivatives(&cone, Ray::new(Point::ORIGIN, dir.normalize()));
+        }
     }
 }
@@ -0,0 +1,334 @@
+//!
+//! Thin curves, for hair, fur and grass.
+//!
+//! A [Curves] object holds a large number of cubic Bézier segments with a varying width, stored in
+//! bulk behind a single [Bvh]. Segments are intersected as ribbons always facing the incoming ray,
+//! which is a good approximation as long as they are thin on screen. The shading normal can
+//! either be the ribbon one, or be bent across the width to fake a cylinder.
+//!
+
+use std::sync::Arc;
+
+use crate::{
+    maths::{Aabb, Bvh, Point, Vector},
+    render::{Intersection, Ray},
+};
+
+use super::{Material, Object};
+
+/// Maximum number of curve subdivisions during intersection.
+const MAX_DEPTH: i32 = 10;
+
+///
+/// Shading normal of [Curves].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveKind {
+    /// Flat ribbon, facing the incoming ray.
+    Flat,
+    /// Ribbon shaded as a cylinder.
+    Cylinder,
+}
+
+///
+/// Cubic Bézier segment, with a width linearly interpolated from its start to its end.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct CurveSegment {
+    points: [Vector; 4],
+    width: [f32; 2],
+}
+
+impl CurveSegment {
+    pub fn new(points: [Point; 4], start_width: f32, end_width: f32) -> Self {
+        Self {
+            points: points.map(|p| p - Point::ORIGIN),
+            width: [start_width, end_width],
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        let hw = self.width[0].max(self.width[1]) / 2.;
+        let b = Aabb::from_points(self.points.map(|p| Point::ORIGIN + p));
+
+        Aabb::new(
+            b.min - Vector::new(hw, hw, hw),
+            b.max + Vector::new(hw, hw, hw),
+        )
+    }
+}
+
+///
+/// Bulk storage of thin curves.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone)]
+pub struct Curves {
+    segments: Arc<[CurveSegment]>,
+    bvh: Arc<Bvh>,
+    kind: CurveKind,
+    material: Material,
+}
+
+impl Curves {
+    pub fn new(segments: Vec<CurveSegment>, kind: CurveKind) -> Self {
+        let boxes: Vec<_> = segments.iter().map(CurveSegment::bounds).collect();
+
+        Self {
+            segments: segments.into(),
+            bvh: Arc::new(Bvh::new(&boxes)),
+            kind,
+            material: Material::DEFAULT,
+        }
+    }
+
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    ///
+    /// Compute the closest intersection between a `Ray` and the curves.
+    ///
+    pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        self.bvh.intersect(ray, |i, max| {
+            self.intersect_segment(ray, &self.segments[i], max)
+        })
+    }
+
+    ///
+    /// Intersect a single segment.
+    ///
+    /// The segment is first moved to the ray space, where the ray starts at the origin and goes
+    /// along Z. The curve is then recursively split in halves, discarding the ones whose bounding
+    /// box doesn't contain the ray, until the remaining pieces are flat enough to be approximated
+    /// by lines (see Nakamaru & Ohno, "Ray Tracing for Curves Primitive").
+    ///
+    fn intersect_segment(&self, ray: Ray, seg: &CurveSegment, max: f32) -> Option<Intersection> {
+        let (bx, by) = ray.dir.any_orthonormal_pair();
+        let to_ray = |p: Vector| {
+            let p = (Point::ORIGIN + p) - ray.orig;
+            Vector::new(p.dot(bx), p.dot(by), p.dot(ray.dir))
+        };
+        let cps = seg.points.map(to_ray);
+
+        // Number of subdivisions needed so that the pieces deviate from a line by less than a
+        // fraction of the curve width.
+        let l0 = (0..2)
+            .map(|i| cps[i] - 2. * cps[i + 1] + cps[i + 2])
+            .map(|d| d.x.abs().max(d.y.abs()).max(d.z.abs()))
+            .fold(0., f32::max);
+        let eps = seg.width[0].max(seg.width[1]) / 20.;
+        let depth = match (std::f32::consts::SQRT_2 * 6. * l0 / (8. * eps)).log2() / 2. {
+            r if r.is_finite() => (r.round() as i32).clamp(0, MAX_DEPTH),
+            _ => 0,
+        };
+
+        let (t, u) = recurse(cps, seg.width, (0., 1.), depth, max)?;
+
+        let pos = ray.orig + ray.dir * t;
+        let (center, tangent) = eval(seg.points, u);
+        let width = seg.width[0] + (seg.width[1] - seg.width[0]) * u;
+
+        let facing = -ray.dir;
+        let normal = match self.kind {
+            CurveKind::Flat => facing,
+            CurveKind::Cylinder => {
+                let side = tangent.cross(facing).normalize();
+                let offset =
+                    ((pos - (Point::ORIGIN + center)).dot(side) * 2. / width).clamp(-1., 1.);
+
+                facing * (1. - offset.powi(2)).sqrt() + side * offset
+            }
+        };
+
+        Some(Intersection::new(t, pos, normal))
+    }
+}
+
+///
+/// Recursive part of the segment intersection, in ray space.
+///
+/// `cps` are the control points of the piece of curve spanning `range` of the whole segment, and
+/// `width` the curve width at both ends of the piece. Returns the distance along the ray and the
+/// segment parameter of the closest hit.
+///
+fn recurse(
+    cps: [Vector; 4],
+    width: [f32; 2],
+    range: (f32, f32),
+    depth: i32,
+    max: f32,
+) -> Option<(f32, f32)> {
+    let hw = width[0].max(width[1]) / 2.;
+    let Aabb { min, max: top } = Aabb::from_points(cps.map(|p| Point::ORIGIN + p));
+
+    if min.x - hw > 0. || top.x + hw < 0. || min.y - hw > 0. || top.y + hw < 0. {
+        return None;
+    }
+    if min.z - hw > max || top.z + hw < 0. {
+        return None;
+    }
+
+    if depth > 0 {
+        let [left, right] = split(cps);
+        let mid_width = (width[0] + width[1]) / 2.;
+        let mid = (range.0 + range.1) / 2.;
+
+        let first = recurse(left, [width[0], mid_width], (range.0, mid), depth - 1, max);
+        let max = first.map_or(max, |(t, _)| t);
+        let second = recurse(right, [mid_width, width[1]], (mid, range.1), depth - 1, max);
+
+        return second.or(first);
+    }
+
+    // The ray must lie between the lines perpendicular to the curve at each end of the piece.
+    let start = (cps[1].x - cps[0].x) * -cps[0].x + (cps[1].y - cps[0].y) * -cps[0].y;
+    let end = (cps[2].x - cps[3].x) * -cps[3].x + (cps[2].y - cps[3].y) * -cps[3].y;
+
+    if start < 0. || end < 0. {
+        return None;
+    }
+
+    // Project the ray on the line going through the piece end points.
+    let seg = cps[3] - cps[0];
+    let len2 = seg.x.powi(2) + seg.y.powi(2);
+    let s = if len2 > 0. {
+        ((-cps[0].x * seg.x - cps[0].y * seg.y) / len2).clamp(0., 1.)
+    } else {
+        0.
+    };
+
+    let (hit, _) = eval(cps, s);
+    let w = width[0] + (width[1] - width[0]) * s;
+
+    if hit.x.powi(2) + hit.y.powi(2) > (w / 2.).powi(2) || hit.z <= 1e-4 || hit.z >= max {
+        return None;
+    }
+
+    Some((hit.z, range.0 + (range.1 - range.0) * s))
+}
+
+///
+/// Evaluate a cubic Bézier curve, and its derivative, at `t`.
+///
+fn eval(cps: [Vector; 4], t: f32) -> (Vector, Vector) {
+    let s = 1. - t;
+
+    let pos = cps[0] * (s * s * s)
+        + cps[1] * (3. * t * s * s)
+        + cps[2] * (3. * t * t * s)
+        + cps[3] * (t * t * t);
+    let deriv = (cps[1] - cps[0]) * (3. * s * s)
+        + (cps[2] - cps[1]) * (6. * t * s)
+        + (cps[3] - cps[2]) * (3. * t * t);
+
+    (pos, deriv)
+}
+
+///
+/// Split a cubic Bézier curve in two halves, using de Casteljau's algorithm.
+///
+fn split(cps: [Vector; 4]) -> [[Vector; 4]; 2] {
+    let mid = |a: Vector, b: Vector| (a + b) / 2.;
+
+    let p01 = mid(cps[0], cps[1]);
+    let p12 = mid(cps[1], cps[2]);
+    let p23 = mid(cps[2], cps[3]);
+    let p012 = mid(p01, p12);
+    let p123 = mid(p12, p23);
+    let p = mid(p012, p123);
+
+    [[cps[0], p01, p012, p], [p, p123, p23, cps[3]]]
+}
+
+impl Object for Curves {
+    fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        self.intersect(ray)
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn cloned(&self) -> Box<dyn Object> {
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Straight segment along X, from `x = -2` to `x = 2`, at `z = depth`, with a width of 0.5.
+    ///
+    fn straight(depth: f32) -> CurveSegment {
+        CurveSegment::new(
+            [-2., -2. / 3., 2. / 3., 2.].map(|x| Point::new(x, 0., depth)),
+            0.5,
+            0.5,
+        )
+    }
+
+    #[test]
+    fn flat_hit_test() {
+        let curves = Curves::new(vec![straight(5.)], CurveKind::Flat);
+        let inter = curves
+            .intersect(Ray::new(Point::new(1., 0.2, 0.), Vector::Z))
+            .unwrap();
+
+        assert!((inter.dist - 5.).abs() <= 1e-3);
+        assert!((inter.normal + Vector::Z).magn() <= 1e-4);
+    }
+
+    #[test]
+    fn miss_test() {
+        let curves = Curves::new(vec![straight(5.)], CurveKind::Flat);
+
+        // Beside the ribbon, past its end, and behind the ray.
+        assert!(curves
+            .intersect(Ray::new(Point::new(0., 0.3, 0.), Vector::Z))
+            .is_none());
+        assert!(curves
+            .intersect(Ray::new(Point::new(2.5, 0., 0.), Vector::Z))
+            .is_none());
+        assert!(curves
+            .intersect(Ray::new(Point::ORIGIN, -Vector::Z))
+            .is_none());
+    }
+
+    #[test]
+    fn closest_segment_test() {
+        let curves = Curves::new(vec![straight(8.), straight(5.)], CurveKind::Flat);
+        let inter = curves
+            .intersect(Ray::new(Point::ORIGIN, Vector::Z))
+            .unwrap();
+
+        assert!((inter.dist - 5.).abs() <= 1e-3);
+    }
+
+    #[test]
+    fn cylinder_normal_test() {
+        let curves = Curves::new(vec![straight(5.)], CurveKind::Cylinder);
+
+        // Half way from the axis to the edge, the normal is tilted by 30° toward the edge.
+        let inter = curves
+            .intersect(Ray::new(Point::new(0., 0.125, 0.), Vector::Z))
+            .unwrap();
+        let expected = Vector::new(0., 0.5, -(0.75f32).sqrt());
+
+        assert!((inter.normal - expected).magn() <= 1e-3);
+
+        // On the axis, it faces the ray.
+        let inter = curves
+            .intersect(Ray::new(Point::ORIGIN, Vector::Z))
+            .unwrap();
+
+        assert!((inter.normal + Vector::Z).magn() <= 1e-3);
+    }
+}
@@ -7,16 +7,17 @@ use crate::{
     render::{Intersection, Ray},
 };
 
-use super::Object;
+use super::{Material, Object};
 
 ///
 /// Infinite Cylinder
 ///
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Cylinder {
     pos: Point,
     dir: Vector,
     radius: f32,
+    material: Material,
 }
 
 impl Cylinder {
@@ -25,9 +26,24 @@ impl Cylinder {
             pos,
             dir: dir.normalize(),
             radius,
+            material: Material::DEFAULT,
         }
     }
 
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    ///
+    /// Compute the intersection between a [Ray] and the cylinder.
+    ///
+    /// Texture coordinates use a cylindrical mapping: `u` goes around the axis, and `v` is the
+    /// distance along the axis from the cylinder position.
+    ///
     pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
         let adj = ray.orig - self.pos;
 
@@ -44,7 +60,9 @@ impl Cylinder {
             let point = ray.orig + ray.dir * dist;
             let projection = self.pos + self.dir * (point - self.pos).dot(self.dir);
 
-            Some(Intersection::new(dist, point, point - projection))
+            let (uv, dpdu) = super::cylindrical_mapping(point, self.pos, self.dir);
+
+            Some(Intersection::new(dist, point, point - projection).with_uv(uv, dpdu, self.dir))
         } else {
             None
         }
@@ -56,7 +74,42 @@ impl Object for Cylinder {
         self.intersect(ray)
     }
 
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
     fn cloned(&self) -> Box<dyn Object> {
-        Box::new(*self) as Box<dyn Object>
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::scene::objects::tests::assert_uv_derivatives;
+
+    #[test]
+    fn uv_test() {
+        let cylinder = Cylinder::new(Point::new(0., 0., 5.), Vector::Y, 2.);
+        let (b, t) = Vector::Y.any_orthonormal_pair();
+
+        // `v` is the height along the axis, and `u` is 0.5 in the direction of `t`.
+        let orig = Point::new(0., 3., 5.) + t * 10.;
+        let inter = cylinder.intersect(Ray::new(orig, -t)).unwrap();
+
+        assert!((inter.uv.0 - 0.5).abs() <= 1e-6 && (inter.uv.1 - 3.).abs() <= 1e-5);
+        assert!((inter.dpdu - b * (4. * PI)).magn() <= 1e-4);
+        assert_eq!(inter.dpdv, Vector::Y);
+    }
+
+    #[test]
+    fn uv_derivatives_test() {
+        let cylinder = Cylinder::new(Point::new(0., 0., 5.), Vector::new(0.2, 1., 0.1), 2.);
+
+        for dir in [Vector::new(0.2, 0.1, 1.), Vector::new(-0.3, 0.5, 1.)] {
+            assert_uv_derivatives(&cylinder, Ray::new(Point::ORIGIN, dir.normalize()));
+        }
     }
 }
@@ -0,0 +1,383 @@
+//!
+//! Heightfield terrain.
+//!
+//! A heightfield is a regular 2D grid of heights, laid on the XZ plane, with the Y axis pointing
+//! up. Each grid cell is made of two triangles, and shading normals are interpolated from the
+//! grid vertices, which gives smooth looking landscapes from a coarse grid.
+//!
+
+use std::{path::Path, sync::Arc};
+
+use nannou::image::{self, ImageResult};
+
+use crate::{
+    maths::{Aabb, Point, Vector},
+    render::{Intersection, Ray},
+};
+
+use super::{Material, Object};
+
+///
+/// Heightfield terrain.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone)]
+pub struct Heightfield {
+    pos: Point,
+    /// Number of grid vertices along X.
+    width: usize,
+    /// Number of grid vertices along Z.
+    depth: usize,
+    /// Size of a grid cell along X and Z.
+    cell: (f32, f32),
+
+    /// Heights, in world units, stored row by row.
+    heights: Arc<[f32]>,
+    /// Vertex normals, stored like `heights`.
+    normals: Arc<[Vector]>,
+    /// Highest point of each cell, used to skip cells the ray flies over.
+    cell_max: Arc<[f32]>,
+
+    bounds: Aabb,
+    material: Material,
+}
+
+impl Heightfield {
+    ///
+    /// Create a new heightfield.
+    ///
+    /// `pos` is the position of the grid corner, and `size` its extent: the grid spans
+    /// `size.x * size.z`, and the heights, expected in `[0, 1]`, are scaled by `size.y`.
+    /// `heights` is a row-major grid of `width * depth` values, rows being parallel to X.
+    ///
+    /// # Panics
+    /// Panics if the grid is smaller than 2x2, or if `heights` doesn't match its size.
+    ///
+    pub fn new(pos: Point, size: Vector, width: usize, depth: usize, heights: Vec<f32>) -> Self {
+        assert!(
+            width >= 2 && depth >= 2,
+            "Heightfield::new: grid must be at least 2x2."
+        );
+        assert_eq!(
+            heights.len(),
+            width * depth,
+            "Heightfield::new: heights doesn't match the grid size."
+        );
+
+        let cell = (size.x / (width - 1) as f32, size.z / (depth - 1) as f32);
+        let heights: Vec<f32> = heights.into_iter().map(|h| h * size.y).collect();
+
+        let at = |x: usize, z: usize| heights[z * width + x];
+
+        let normals: Vec<Vector> = (0..depth)
+            .flat_map(|z| (0..width).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(width - 1));
+                let (z0, z1) = (z.saturating_sub(1), (z + 1).min(depth - 1));
+
+                let dx = (at(x1, z) - at(x0, z)) / ((x1 - x0) as f32 * cell.0);
+                let dz = (at(x, z1) - at(x, z0)) / ((z1 - z0) as f32 * cell.1);
+
+                Vector::new(-dx, 1., -dz).normalize()
+            })
+            .collect();
+
+        let cell_max = (0..depth - 1)
+            .flat_map(|z| (0..width - 1).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                at(x, z)
+                    .max(at(x + 1, z))
+                    .max(at(x, z + 1))
+                    .max(at(x + 1, z + 1))
+            })
+            .collect();
+
+        let (min_h, max_h) = heights
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), h| {
+                (min.min(*h), max.max(*h))
+            });
+
+        let bounds = Aabb::new(
+            pos + Vector::new(0., min_h, 0.),
+            pos + Vector::new(size.x, max_h, size.z),
+        );
+
+        Self {
+            pos,
+            width,
+            depth,
+            cell,
+            heights: heights.into(),
+            normals: normals.into(),
+            cell_max,
+            bounds,
+            material: Material::DEFAULT,
+        }
+    }
+
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    ///
+    /// Create a procedural heightfield.
+    ///
+    /// `f` is sampled on each grid vertex with normalized coordinates `(u, v)` in `[0, 1]`, and
+    /// should return a height in `[0, 1]`.
+    ///
+    pub fn from_fn(
+        pos: Point,
+        size: Vector,
+        width: usize,
+        depth: usize,
+        f: impl Fn(f32, f32) -> f32,
+    ) -> Self {
+        let heights = (0..depth)
+            .flat_map(|z| (0..width).map(move |x| (x, z)))
+            .map(|(x, z)| f(x as f32 / (width - 1) as f32, z as f32 / (depth - 1) as f32))
+            .collect();
+
+        Self::new(pos, size, width, depth, heights)
+    }
+
+    ///
+    /// Load a heightfield from a grayscale image.
+    ///
+    /// Each pixel becomes a grid vertex, black being the lowest point and white the highest. The
+    /// top row of the image is laid at the far end of the grid (the highest Z).
+    ///
+    pub fn from_image(path: impl AsRef<Path>, pos: Point, size: Vector) -> ImageResult<Self> {
+        let img = image::open(path)?.to_luma16();
+        let (width, depth) = (img.width() as usize, img.height() as usize);
+
+        let heights = (0..depth)
+            .flat_map(|z| (0..width).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                let px = img.get_pixel(x as u32, (depth - 1 - z) as u32);
+                px.0[0] as f32 / u16::MAX as f32
+            })
+            .collect();
+
+        Ok(Self::new(pos, size, width, depth, heights))
+    }
+
+    fn vertex(&self, x: usize, z: usize) -> (Point, Vector) {
+        let idx = z * self.width + x;
+        let p = self.pos
+            + Vector::new(
+                x as f32 * self.cell.0,
+                self.heights[idx],
+                z as f32 * self.cell.1,
+            );
+
+        (p, self.normals[idx])
+    }
+
+    ///
+    /// Intersect the two triangles of the cell `(x, z)`.
+    ///
+    fn intersect_cell(&self, ray: Ray, x: usize, z: usize) -> Option<Intersection> {
+        let v00 = self.vertex(x, z);
+        let v10 = self.vertex(x + 1, z);
+        let v01 = self.vertex(x, z + 1);
+        let v11 = self.vertex(x + 1, z + 1);
+
+        [(v00, v10, v11), (v00, v11, v01)]
+            .into_iter()
+            .filter_map(|(a, b, c)| {
+                intersect_triangle(ray, a.0, b.0, c.0).map(|(t, u, v)| {
+                    let normal = a.1 * (1. - u - v) + b.1 * u + c.1 * v;
+                    Intersection::new(t, ray.orig + ray.dir * t, normal)
+                })
+            })
+            .min_by(|i, i2| i.dist.partial_cmp(&i2.dist).unwrap())
+    }
+
+    ///
+    /// Compute the closest intersection between a `Ray` and the heightfield.
+    ///
+    /// The ray is first clipped by the heightfield bounding box. The grid cells crossed by the
+    /// ray are then walked in order, using a 2D DDA (see Amanatides & Woo, "A Fast Voxel
+    /// Traversal Algorithm"), so the first hit is the closest one. Cells whose highest point is
+    /// below the ray are skipped without testing their triangles.
+    ///
+    pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        let (near, far) = self.bounds.intersect(ray)?;
+        let near = near.max(0.);
+
+        let local = ray.orig + ray.dir * near - self.pos;
+        let cells = (self.width - 1, self.depth - 1);
+
+        let cell_of = |p: f32, size: f32, count: usize| ((p / size) as usize).min(count - 1);
+        let mut x = cell_of(local.x.max(0.), self.cell.0, cells.0);
+        let mut z = cell_of(local.z.max(0.), self.cell.1, cells.1);
+
+        // Distance along the ray to cross a whole cell, and to reach the next cell boundary.
+        let axis = |dir: f32, pos: f32, size: f32, idx: usize| {
+            if dir > 0. {
+                (size / dir, ((idx + 1) as f32 * size - pos) / dir)
+            } else if dir < 0. {
+                (-size / dir, (idx as f32 * size - pos) / dir)
+            } else {
+                (f32::INFINITY, f32::INFINITY)
+            }
+        };
+        let (delta_x, mut next_x) = axis(ray.dir.x, local.x, self.cell.0, x);
+        let (delta_z, mut next_z) = axis(ray.dir.z, local.z, self.cell.1, z);
+
+        let mut t = near;
+
+        loop {
+            let exit = next_x.min(next_z) + near;
+            let y_in = ray.orig.y + ray.dir.y * t;
+            let y_out = ray.orig.y + ray.dir.y * exit.min(far);
+
+            if y_in.min(y_out) <= self.pos.y + self.cell_max[z * cells.0 + x] {
+                if let Some(inter) = self.intersect_cell(ray, x, z) {
+                    return Some(inter);
+                }
+            }
+
+            if exit > far {
+                return None;
+            }
+            t = exit;
+
+            if next_x < next_z {
+                next_x += delta_x;
+                x = match ray.dir.x > 0. {
+                    true if x + 1 < cells.0 => x + 1,
+                    false if x > 0 => x - 1,
+                    _ => return None,
+                };
+            } else {
+                next_z += delta_z;
+                z = match ray.dir.z > 0. {
+                    true if z + 1 < cells.1 => z + 1,
+                    false if z > 0 => z - 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+}
+
+///
+/// Möller–Trumbore ray/triangle intersection.
+///
+/// Returns the distance along the ray, and the barycentric coordinates of the hit relative to `b`
+/// and `c`.
+///
+fn intersect_triangle(ray: Ray, a: Point, b: Point, c: Point) -> Option<(f32, f32, f32)> {
+    let e1 = b - a;
+    let e2 = c - a;
+
+    let p = ray.dir.cross(e2);
+    let det = e1.dot(p);
+
+    if det.abs() < 1e-9 {
+        return None;
+    }
+
+    let inv = det.recip();
+    let s = ray.orig - a;
+    let u = s.dot(p) * inv;
+
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(e1);
+    let v = ray.dir.dot(q) * inv;
+
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+
+    let t = e2.dot(q) * inv;
+
+    if t > 1e-5 {
+        Some((t, u, v))
+    } else {
+        None
+    }
+}
+
+impl Object for Heightfield {
+    fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        self.intersect(ray)
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn cloned(&self) -> Box<dyn Object> {
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slope() -> Heightfield {
+        Heightfield::from_fn(Point::ORIGIN, Vector::new(1., 1., 1.), 5, 5, |x, _| x)
+    }
+
+    #[test]
+    fn flat_hit_test() {
+        let h = Heightfield::new(Point::ORIGIN, Vector::new(2., 2., 2.), 3, 3, vec![0.5; 9]);
+        let inter = h
+            .intersect(Ray::new(Point::new(1.3, 5., 0.7), -Vector::Y))
+            .unwrap();
+
+        assert!((inter.dist - 4.).abs() <= 1e-4);
+        assert!((inter.normal - Vector::Y).magn() <= 1e-4);
+    }
+
+    #[test]
+    fn miss_test() {
+        let h = slope();
+
+        // Outside of the grid, above it, and going away from it.
+        assert!(h
+            .intersect(Ray::new(Point::new(2., 5., 0.5), -Vector::Y))
+            .is_none());
+        assert!(h
+            .intersect(Ray::new(Point::new(-1., 2., 0.5), Vector::X))
+            .is_none());
+        assert!(h
+            .intersect(Ray::new(Point::new(0.5, 2., 0.5), Vector::Y))
+            .is_none());
+    }
+
+    #[test]
+    fn slope_test() {
+        let h = slope();
+        let inter = h
+            .intersect(Ray::new(Point::new(0.6, 5., 0.3), -Vector::Y))
+            .unwrap();
+
+        assert!((inter.dist - 4.4).abs() <= 1e-4);
+        assert!((inter.normal - Vector::new(-1., 1., 0.).normalize()).magn() <= 1e-4);
+    }
+
+    #[test]
+    fn grazing_test() {
+        // The ray crosses several cells before hitting the slope.
+        let h = slope();
+        let inter = h
+            .intersect(Ray::new(Point::new(-1., 0.25, 0.5), Vector::X))
+            .unwrap();
+
+        assert!((inter.dist - 1.25).abs() <= 1e-4);
+        assert!((inter.pos.x - 0.25).abs() <= 1e-4);
+    }
+}
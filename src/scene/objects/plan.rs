@@ -7,15 +7,16 @@ use crate::{
     render::{Intersection, Ray},
 };
 
-use super::Object;
+use super::{Material, Object};
 
 ///
 /// Infinite plan.
 ///
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Plan {
     pos: Point,
     norm: Vector,
+    material: Material,
 }
 
 impl Plan {
@@ -23,9 +24,25 @@ impl Plan {
         Self {
             pos,
             norm: norm.normalize(),
+            material: Material::DEFAULT,
         }
     }
 
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    ///
+    /// Compute the intersection between a [Ray] and the plan.
+    ///
+    /// Texture coordinates tile the plan: they are the coordinates of the hit point in an
+    /// arbitrary orthonormal frame of the plan, centered on its position, so that a texture
+    /// repeats every unit.
+    ///
     pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
         match ray.dir.dot(-self.norm) {
             denom if denom.abs() > 1e-7 => {
@@ -34,10 +51,14 @@ impl Plan {
                 let d = -(p.dot(sign * self.norm)) / denom.abs();
 
                 if d > 1e-7 {
-                    Some(Intersection::new(
-                        d,
-                        ray.orig + d * ray.dir,
-                        sign * self.norm,
+                    let pos = ray.orig + d * ray.dir;
+                    let (b, t) = self.norm.any_orthonormal_pair();
+                    let local = pos - self.pos;
+
+                    Some(Intersection::new(d, pos, sign * self.norm).with_uv(
+                        (local.dot(t), local.dot(b)),
+                        t,
+                        b,
                     ))
                 } else {
                     None
@@ -53,7 +74,46 @@ impl Object for Plan {
         self.intersect(ray)
     }
 
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
     fn cloned(&self) -> Box<dyn Object> {
-        Box::new(*self) as Box<dyn Object>
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::objects::tests::assert_uv_derivatives;
+
+    #[test]
+    fn uv_test() {
+        let norm = Vector::new(0., 1., -1.).normalize();
+        let plan = Plan::new(Point::new(0., 0., 5.), norm);
+        let (b, t) = norm.any_orthonormal_pair();
+
+        // Texture coordinates are the coordinates in the `(t, b)` frame of the plan.
+        let target = Point::new(0., 0., 5.) + t * 1.5 - b * 0.5;
+        let inter = plan
+            .intersect(Ray::new(
+                Point::ORIGIN,
+                (target - Point::ORIGIN).normalize(),
+            ))
+            .unwrap();
+
+        assert!((inter.uv.0 - 1.5).abs() <= 1e-5 && (inter.uv.1 + 0.5).abs() <= 1e-5);
+        assert!((inter.dpdu - t).magn() <= 1e-6 && (inter.dpdv - b).magn() <= 1e-6);
+    }
+
+    #[test]
+    fn uv_derivatives_test() {
+        let plan = Plan::new(Point::new(0., -1., 0.), Vector::new(0.1, 1., 0.2));
+
+        assert_uv_derivatives(
+            &plan,
+            Ray::new(Point::ORIGIN, Vector::new(0.3, -1., 1.).normalize()),
+        );
     }
 }
@@ -0,0 +1,322 @@
+//!
+//! General 3D quadric surface.
+//!
+//! A quadric is any surface defined by an implicit second degree equation:
+//! ```text
+//! Ax^2 + By^2 + Cz^2 + Dxy + Exz + Fyz + Gx + Hy + Iz + J = 0
+//! ```
+//!
+//! Spheres, cylinders and cones are all quadrics, but so are ellipsoids, paraboloids and
+//! hyperboloids.
+//!
+
+use crate::{
+    maths::{Point, Polynom2, Quaternion, Vector},
+    render::{Intersection, Ray},
+};
+
+use super::{Material, Object};
+
+///
+/// Quadric surface, optionally clipped by an axis-aligned box.
+///
+/// The equation is expressed in the quadric local space, where `pos` is the origin and `dir` is
+/// the Z axis.
+///
+#[derive(Debug, Clone)]
+pub struct Quadric {
+    pos: Point,
+    /// Equation coefficients, in the `[A, B, C, D, E, F, G, H, I, J]` order.
+    coefs: [f32; 10],
+    /// Clipping box, in local space.
+    bounds: Option<(Vector, Vector)>,
+
+    /// Rotation from local to world space.
+    rot: Quaternion,
+    material: Material,
+}
+
+impl Quadric {
+    ///
+    /// Create a new quadric from its 10 coefficients.
+    ///
+    /// `coefs` are given in the `[A, B, C, D, E, F, G, H, I, J]` order (see
+    /// [module documentation](self)).
+    ///
+    pub fn new(pos: Point, dir: Vector, coefs: [f32; 10]) -> Self {
+        let dir = dir.normalize();
+
+        Self {
+            pos,
+            coefs,
+            bounds: None,
+            rot: Quaternion::from_arc(Vector::Z, dir),
+            material: Material::DEFAULT,
+        }
+    }
+
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    ///
+    /// Ellipsoid with semi-axes `radii`: `x^2/a^2 + y^2/b^2 + z^2/c^2 = 1`
+    ///
+    pub fn ellipsoid(pos: Point, dir: Vector, radii: Vector) -> Self {
+        let [a, b, c] = Self::inv_sq(radii);
+
+        Self::new(pos, dir, [a, b, c, 0., 0., 0., 0., 0., 0., -1.])
+    }
+
+    ///
+    /// Elliptic paraboloid opening along `dir`: `z = x^2/a^2 + y^2/b^2`
+    ///
+    pub fn elliptic_paraboloid(pos: Point, dir: Vector, a: f32, b: f32) -> Self {
+        let [a, b, _] = Self::inv_sq(Vector::new(a, b, 1.));
+
+        Self::new(pos, dir, [a, b, 0., 0., 0., 0., 0., 0., -1., 0.])
+    }
+
+    ///
+    /// One sheet hyperboloid around `dir`: `x^2/a^2 + y^2/b^2 - z^2/c^2 = 1`
+    ///
+    pub fn hyperboloid_one_sheet(pos: Point, dir: Vector, radii: Vector) -> Self {
+        let [a, b, c] = Self::inv_sq(radii);
+
+        Self::new(pos, dir, [a, b, -c, 0., 0., 0., 0., 0., 0., -1.])
+    }
+
+    ///
+    /// Two sheets hyperboloid along `dir`: `-x^2/a^2 - y^2/b^2 + z^2/c^2 = 1`
+    ///
+    pub fn hyperboloid_two_sheets(pos: Point, dir: Vector, radii: Vector) -> Self {
+        let [a, b, c] = Self::inv_sq(radii);
+
+        Self::new(pos, dir, [-a, -b, c, 0., 0., 0., 0., 0., 0., -1.])
+    }
+
+    ///
+    /// Clip the quadric to the box spanning from `min` to `max`, in local space.
+    ///
+    pub fn with_bounds(mut self, min: Vector, max: Vector) -> Self {
+        self.bounds = Some((min, max));
+        self
+    }
+
+    fn inv_sq(v: Vector) -> [f32; 3] {
+        [v.x.powi(-2), v.y.powi(-2), v.z.powi(-2)]
+    }
+
+    fn in_bounds(&self, p: Vector) -> bool {
+        self.bounds.is_none_or(|(min, max)| {
+            (min.x..=max.x).contains(&p.x)
+                && (min.y..=max.y).contains(&p.y)
+                && (min.z..=max.z).contains(&p.z)
+        })
+    }
+
+    ///
+    /// Compute the quadric gradient at the local point `p`.
+    ///
+    fn gradient(&self, p: Vector) -> Vector {
+        let [a, b, c, d, e, f, g, h, i, _] = self.coefs;
+
+        Vector::new(
+            2. * a * p.x + d * p.y + e * p.z + g,
+            2. * b * p.y + d * p.x + f * p.z + h,
+            2. * c * p.z + e * p.x + f * p.y + i,
+        )
+    }
+
+    ///
+    /// Compute the closest intersection between a `Ray` and the quadric.
+    ///
+    /// The ray is first moved to the quadric local space, then its parametric equation is
+    /// injected in the quadric's one, which gives a second degree polynomial in `k`, the distance
+    /// along the ray:
+    /// ```text
+    /// a = A*dx^2 + B*dy^2 + C*dz^2 + D*dx*dy + E*dx*dz + F*dy*dz
+    /// b = 2*(A*ox*dx + B*oy*dy + C*oz*dz) + D*(ox*dy + oy*dx) + E*(ox*dz + oz*dx)
+    ///     + F*(oy*dz + oz*dy) + G*dx + H*dy + I*dz
+    /// c = Q(o)
+    /// ```
+    ///
+    /// When `a` vanishes (e.g. a ray parallel to a paraboloid axis), the equation is linear.
+    ///
+    /// Roots are checked in ascending order against the clipping bounds, so that a clipped
+    /// quadric can be seen from the inside.
+    ///
+    pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        let inv = self.rot.recip();
+        let o = inv * (ray.orig - self.pos);
+        let d = inv * ray.dir;
+
+        let [qa, qb, qc, qd, qe, qf, qg, qh, qi, qj] = self.coefs;
+
+        let a = qa * d.x * d.x
+            + qb * d.y * d.y
+            + qc * d.z * d.z
+            + qd * d.x * d.y
+            + qe * d.x * d.z
+            + qf * d.y * d.z;
+        let b = 2. * (qa * o.x * d.x + qb * o.y * d.y + qc * o.z * d.z)
+            + qd * (o.x * d.y + o.y * d.x)
+            + qe * (o.x * d.z + o.z * d.x)
+            + qf * (o.y * d.z + o.z * d.y)
+            + qg * d.x
+            + qh * d.y
+            + qi * d.z;
+        let c = qa * o.x * o.x
+            + qb * o.y * o.y
+            + qc * o.z * o.z
+            + qd * o.x * o.y
+            + qe * o.x * o.z
+            + qf * o.y * o.z
+            + qg * o.x
+            + qh * o.y
+            + qi * o.z
+            + qj;
+
+        let roots = if a.abs() <= 1e-7 {
+            if b.abs() <= 1e-7 {
+                return None;
+            }
+            [-c / b, f32::NAN]
+        } else {
+            Polynom2::new(a, b, c).roots()?
+        };
+
+        roots
+            .into_iter()
+            .filter(|r| !r.is_nan() && r.is_sign_positive())
+            .map(|r| (r, o + d * r))
+            .find(|(_, p)| self.in_bounds(*p))
+            .map(|(dist, p)| {
+                let normal = self.rot * self.gradient(p);
+
+                Intersection::new(dist, ray.orig + ray.dir * dist, normal)
+            })
+    }
+}
+
+impl Object for Quadric {
+    fn intersect(&self, ray: Ray) -> Option<Intersection> {
+        self.intersect(ray)
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn cloned(&self) -> Box<dyn Object> {
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vector, b: Vector) {
+        assert!((a - b).magn() <= 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn ellipsoid_hit_test() {
+        let q = Quadric::ellipsoid(Point::new(0., 0., 5.), Vector::Z, Vector::new(1., 2., 3.));
+        let inter = q.intersect(Ray::new(Point::ORIGIN, Vector::Z)).unwrap();
+
+        assert!((inter.dist - 2.).abs() <= 1e-4);
+        assert_near(inter.normal, -Vector::Z);
+    }
+
+    #[test]
+    fn ellipsoid_side_normal_test() {
+        let q = Quadric::ellipsoid(Point::new(0., 0., 5.), Vector::Z, Vector::new(1., 2., 3.));
+        let inter = q
+            .intersect(Ray::new(Point::new(0., 5., 5.), -Vector::Y))
+            .unwrap();
+
+        assert!((inter.dist - 3.).abs() <= 1e-4);
+        assert_near(inter.normal, Vector::Y);
+    }
+
+    #[test]
+    fn ellipsoid_miss_test() {
+        let q = Quadric::ellipsoid(Point::new(0., 0., 5.), Vector::Z, Vector::new(1., 2., 3.));
+
+        assert!(q.intersect(Ray::new(Point::ORIGIN, -Vector::Z)).is_none());
+        assert!(q
+            .intersect(Ray::new(Point::new(1.5, 0., 0.), Vector::Z))
+            .is_none());
+    }
+
+    #[test]
+    fn paraboloid_axis_test() {
+        // The ray is parallel to the axis, which gives a linear equation.
+        let q = Quadric::elliptic_paraboloid(Point::ORIGIN, Vector::Z, 1., 1.);
+        let inter = q
+            .intersect(Ray::new(Point::new(1., 0., -1.), Vector::Z))
+            .unwrap();
+
+        assert!((inter.dist - 2.).abs() <= 1e-4);
+        assert_near(inter.normal, Vector::new(1., 0., -0.5).normalize());
+    }
+
+    #[test]
+    fn hyperboloid_test() {
+        let one = Quadric::hyperboloid_one_sheet(Point::ORIGIN, Vector::Z, Vector::new(1., 1., 1.));
+        let inter = one
+            .intersect(Ray::new(Point::new(-5., 0., 0.), Vector::X))
+            .unwrap();
+
+        assert!((inter.dist - 4.).abs() <= 1e-4);
+        assert_near(inter.normal, -Vector::X);
+
+        // The two sheets hyperboloid doesn't cross the `z = 0` plane.
+        let two =
+            Quadric::hyperboloid_two_sheets(Point::ORIGIN, Vector::Z, Vector::new(1., 1., 1.));
+        assert!(two
+            .intersect(Ray::new(Point::new(-5., 0., 0.), Vector::X))
+            .is_none());
+
+        let inter = two
+            .intersect(Ray::new(Point::new(0., 0., -5.), Vector::Z))
+            .unwrap();
+        assert!((inter.dist - 4.).abs() <= 1e-4);
+        assert_near(inter.normal, -Vector::Z);
+    }
+
+    #[test]
+    fn bounds_test() {
+        let q = Quadric::hyperboloid_one_sheet(Point::ORIGIN, Vector::Z, Vector::new(1., 1., 1.))
+            .with_bounds(Vector::new(-5., -5., -1.), Vector::new(5., 5., 1.));
+
+        assert!(q
+            .intersect(Ray::new(Point::new(-5., 0., 3.), Vector::X))
+            .is_none());
+
+        // The ray enters above the clipped part, and hits the inner side of the surface.
+        let dir = Vector::new(1., 0., -0.5);
+        let inter = q
+            .intersect(Ray::new(Point::new(-5., 0., 3.), dir.normalize()))
+            .unwrap();
+
+        assert!((inter.dist - 6. * dir.magn()).abs() <= 1e-3);
+        assert_near(inter.normal, Vector::X);
+    }
+
+    #[test]
+    fn rotated_test() {
+        let q = Quadric::ellipsoid(Point::new(5., 0., 0.), Vector::X, Vector::new(1., 1., 3.));
+        let inter = q.intersect(Ray::new(Point::ORIGIN, Vector::X)).unwrap();
+
+        // The long axis follows `dir`.
+        assert!((inter.dist - 2.).abs() <= 1e-4);
+        assert_near(inter.normal, -Vector::X);
+    }
+}
@@ -2,25 +2,40 @@
 //! Basic 3D sphere.
 //!
 
+use std::f32::consts::PI;
+
 use crate::{
-    maths::{Point, Polynom2},
+    maths::{Point, Polynom2, Vector},
     render::{Intersection, Ray},
 };
 
-use super::Object;
+use super::{Material, Object};
 
 ///
 /// Simple Sphere.
 ///
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Sphere {
     pos: Point,
     radius: f32,
+    material: Material,
 }
 
 impl Sphere {
     pub fn new(pos: Point, radius: f32) -> Self {
-        Self { pos, radius }
+        Self {
+            pos,
+            radius,
+            material: Material::DEFAULT,
+        }
+    }
+
+    ///
+    /// Set the object [Material].
+    ///
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
     }
 
     /// Compute the closes intersection between a Ray and a Sphere.
@@ -43,7 +58,7 @@ impl Sphere {
     /// - `R` as the sphere radius.
     ///
     /// After factorization, we find this 2nd degree polynomial:
-    /// ```
+    /// ```text
     /// adj = rpos - spos
     ///
     /// a = ||rdir||^2;
@@ -60,6 +75,9 @@ impl Sphere {
     /// From this, we can compute the exact intersection, as well as the surface normal at this
     /// point.
     ///
+    /// Texture coordinates use a spherical mapping: `u` goes around the `Y` axis, starting and
+    /// ending at `-Z`, and `v` goes from `0` at the bottom pole to `1` at the top pole.
+    ///
     pub fn intersect(&self, ray: Ray) -> Option<Intersection> {
         let adj = ray.orig - self.pos;
 
@@ -75,8 +93,21 @@ impl Sphere {
             .map(|d| {
                 let pos = ray.orig + ray.dir * d;
                 let normal = pos - self.pos;
+                let n = normal / self.radius;
+
+                let u = 0.5 + n.x.atan2(n.z) / (2. * PI);
+                let v = 0.5 + n.y.clamp(-1., 1.).asin() / PI;
 
-                Intersection::new(d, pos, normal)
+                // Derivatives along a parallel and a meridian of the sphere.
+                let cos = (n.x * n.x + n.z * n.z).sqrt();
+                let dpdu = Vector::new(n.z, 0., -n.x) * (2. * PI * self.radius);
+                let dpdv = if cos > 0. {
+                    Vector::new(-n.x * n.y, cos * cos, -n.y * n.z) * (PI * self.radius / cos)
+                } else {
+                    Vector::ZERO
+                };
+
+                Intersection::new(d, pos, normal).with_uv((u, v), dpdu, dpdv)
             })
     }
 }
@@ -86,7 +117,52 @@ impl Object for Sphere {
         self.intersect(ray)
     }
 
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
     fn cloned(&self) -> Box<dyn Object> {
-        Box::new(*self) as Box<dyn Object>
+        Box::new(self.clone()) as Box<dyn Object>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::objects::tests::assert_uv_derivatives;
+
+    #[test]
+    fn uv_test() {
+        let sphere = Sphere::new(Point::new(0., 0., 5.), 2.);
+
+        // Right side of the equator.
+        let inter = sphere
+            .intersect(Ray::new(Point::new(5., 0., 5.), -Vector::X))
+            .unwrap();
+        assert!((inter.uv.0 - 0.75).abs() <= 1e-6 && (inter.uv.1 - 0.5).abs() <= 1e-6);
+        assert!((inter.dpdu + Vector::Z * (4. * PI)).magn() <= 1e-4);
+        assert!((inter.dpdv - Vector::Y * (2. * PI)).magn() <= 1e-4);
+
+        // Front of the sphere, seen from the origin.
+        let inter = sphere
+            .intersect(Ray::new(Point::ORIGIN, Vector::Z))
+            .unwrap();
+        assert!((inter.uv.0 - 0.5).abs() > 0.49 && (inter.uv.1 - 0.5).abs() <= 1e-6);
+
+        // Top pole.
+        let inter = sphere
+            .intersect(Ray::new(Point::new(0., 5., 5.), -Vector::Y))
+            .unwrap();
+        assert!((inter.uv.1 - 1.).abs() <= 1e-6);
+        assert_eq!(inter.dpdv, Vector::ZERO);
+    }
+
+    #[test]
+    fn uv_derivatives_test() {
+        let sphere = Sphere::new(Point::new(0., 0., 5.), 2.);
+
+        for dir in [Vector::new(0.2, 0.1, 1.), Vector::new(-0.3, 0.25, 1.)] {
+            assert_uv_derivatives(&sphere, Ray::new(Point::ORIGIN, dir.normalize()));
+        }
     }
 }
@@ -0,0 +1,96 @@
+//!
+//! Textures.
+//!
+//! A [Texture] gives a color for each point of a surface, from the surface texture coordinates or
+//! the position of an [Intersection]. Textures drive the channels of a
+//! [Material](super::Material), such as its base color or its roughness.
+//!
+
+pub mod image;
+pub mod procedural;
+
+pub use self::image::{Filtering, ImageTexture, Wrap};
+pub use procedural::{Pattern, Procedural, Space};
+
+use std::sync::Arc;
+
+use crate::{colors::Color, render::Intersection};
+
+///
+/// Surface texture.
+///
+/// Colors are in linear space.
+///
+/// Images are shared between the clones of a texture, and compared by address.
+///
+#[derive(Debug, Clone)]
+pub enum Texture {
+    /// Same color on the whole surface.
+    Constant(Color),
+    /// Image, mapped using the surface texture coordinates.
+    Image(Arc<ImageTexture>),
+    /// Pattern computed on the fly.
+    Procedural(Procedural),
+}
+
+impl Texture {
+    pub fn image(image: ImageTexture) -> Self {
+        Self::Image(Arc::new(image))
+    }
+
+    ///
+    /// Return the color of the texture at an [Intersection].
+    ///
+    pub fn eval(&self, inter: &Intersection) -> Color {
+        match self {
+            Self::Constant(color) => *color,
+            Self::Image(image) => image.sample(inter.uv, inter.uv_footprint()),
+            Self::Procedural(procedural) => procedural.eval(inter),
+        }
+    }
+}
+
+impl PartialEq for Texture {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Constant(a), Self::Constant(b)) => a == b,
+            (Self::Image(a), Self::Image(b)) => Arc::ptr_eq(a, b),
+            (Self::Procedural(a), Self::Procedural(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl From<Color> for Texture {
+    fn from(color: Color) -> Self {
+        Self::Constant(color)
+    }
+}
+
+impl From<ImageTexture> for Texture {
+    fn from(image: ImageTexture) -> Self {
+        Self::image(image)
+    }
+}
+
+impl From<Procedural> for Texture {
+    fn from(procedural: Procedural) -> Self {
+        Self::Procedural(procedural)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_handle_test() {
+        let image = || ImageTexture::new(1, 1, vec![Color::WHITE]);
+        let texture = Texture::image(image());
+
+        // Clones share the image, while another image is a different texture.
+        assert_eq!(texture.clone(), texture);
+        assert_ne!(Texture::image(image()), texture);
+        assert_ne!(Texture::from(Color::WHITE), texture);
+    }
+}
@@ -0,0 +1,385 @@
+//!
+//! Image textures.
+//!
+//! An [ImageTexture] maps an image on a surface: the texture coordinates `(0, 0)` are the
+//! bottom-left corner of the image, and `(1, 1)` its top-right corner. Coordinates outside of
+//! `[0, 1]` are brought back into the image according to the [Wrap] mode.
+//!
+//! Images have a finite resolution, so looking up a single texel is either blocky when the
+//! texture is magnified, or aliased when it is minified:
+//! - [Bilinear](Filtering::Bilinear) filtering smoothly interpolates between texels.
+//! - Mipmapping precomputes downscaled copies of the image, each half the size of the previous
+//!   one. The copy whose texels match the area covered by a ray is then sampled, and adjacent
+//!   copies are blended.
+//!
+
+use std::path::Path;
+
+use nannou::image::{self, ImageResult};
+
+use crate::{colors::Color, render::shader::GammaEncoder};
+
+///
+/// Texel lookup method.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filtering {
+    /// Use the closest texel.
+    Nearest,
+    /// Interpolate between the four closest texels.
+    #[default]
+    Bilinear,
+}
+
+///
+/// Handling of texture coordinates outside of `[0, 1]`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Wrap {
+    /// Tile the image.
+    #[default]
+    Repeat,
+    /// Extend the border texels.
+    Clamp,
+    /// Tile the image, flipping every other tile.
+    Mirror,
+}
+
+impl Wrap {
+    ///
+    /// Bring the texel index `i` back into `[0, size)`.
+    ///
+    fn apply(&self, i: i64, size: usize) -> usize {
+        let size = size as i64;
+
+        let i = match self {
+            Self::Repeat => i.rem_euclid(size),
+            Self::Clamp => i.clamp(0, size - 1),
+            Self::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size {
+                    i
+                } else {
+                    2 * size - 1 - i
+                }
+            }
+        };
+
+        i as usize
+    }
+}
+
+///
+/// Single resolution of an image.
+///
+#[derive(Debug, Clone)]
+struct Level {
+    width: usize,
+    height: usize,
+    /// Texels, row by row, from the top row.
+    texels: Vec<Color>,
+}
+
+impl Level {
+    ///
+    /// Downscale the level by a factor of 2, averaging blocks of 2x2 texels.
+    ///
+    fn downscale(&self) -> Self {
+        let (width, height) = ((self.width / 2).max(1), (self.height / 2).max(1));
+        let at = |x: usize, y: usize| {
+            self.texels[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+        };
+
+        let texels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                (at(2 * x, 2 * y)
+                    + at(2 * x + 1, 2 * y)
+                    + at(2 * x, 2 * y + 1)
+                    + at(2 * x + 1, 2 * y + 1))
+                    / 4.
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            texels,
+        }
+    }
+}
+
+///
+/// Texture read from an image.
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone)]
+pub struct ImageTexture {
+    /// Full resolution image, followed by its mipmaps if enabled.
+    levels: Vec<Level>,
+    filtering: Filtering,
+    wrap: Wrap,
+}
+
+impl ImageTexture {
+    ///
+    /// Create a new texture, from a `width × height` image of linear colors, stored row by row
+    /// from the top row.
+    ///
+    pub fn new(width: usize, height: usize, texels: Vec<Color>) -> Self {
+        assert!(
+            width > 0,
+            "ImageTexture::new: width must be a positive integer."
+        );
+        assert!(
+            height > 0,
+            "ImageTexture::new: height must be a positive integer."
+        );
+        assert_eq!(
+            texels.len(),
+            width * height,
+            "ImageTexture::new: expected width × height texels."
+        );
+
+        Self {
+            levels: vec![Level {
+                width,
+                height,
+                texels,
+            }],
+            filtering: Default::default(),
+            wrap: Default::default(),
+        }
+    }
+
+    ///
+    /// Load a color texture from an image file (e.g. PNG or JPEG).
+    ///
+    /// Image files store gamma encoded colors, which are decoded to linear space.
+    ///
+    pub fn open(path: impl AsRef<Path>) -> ImageResult<Self> {
+        let encoder = GammaEncoder::default();
+
+        Self::open_with(path, |c| encoder.decode(c))
+    }
+
+    ///
+    /// Load a data texture from an image file (e.g. a roughness map), without gamma decoding.
+    ///
+    pub fn open_linear(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Self::open_with(path, |c| c)
+    }
+
+    fn open_with(path: impl AsRef<Path>, convert: impl Fn(Color) -> Color) -> ImageResult<Self> {
+        let img = image::open(path)?.to_rgba8();
+        let texels = img
+            .pixels()
+            .map(|px| {
+                let [r, g, b, a] = px.0;
+                convert(Color::new(r as f32, g as f32, b as f32, a as f32) / 255.)
+            })
+            .collect();
+
+        Ok(Self::new(
+            img.width() as usize,
+            img.height() as usize,
+            texels,
+        ))
+    }
+
+    pub fn with_filtering(mut self, filtering: Filtering) -> Self {
+        self.filtering = filtering;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    ///
+    /// Enable mipmapping, computing the downscaled copies of the image.
+    ///
+    pub fn with_mipmaps(mut self) -> Self {
+        self.levels.truncate(1);
+
+        while let Some(last) = self.levels.last().filter(|l| l.width > 1 || l.height > 1) {
+            let next = last.downscale();
+            self.levels.push(next);
+        }
+
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    ///
+    /// Return the color of the texture at the texture coordinates `uv`.
+    ///
+    /// `footprint` is the width of the area covered by a ray, in texture space. When mipmapping
+    /// is enabled, it selects the levels being sampled. Otherwise, it is ignored.
+    ///
+    pub fn sample(&self, uv: (f32, f32), footprint: f32) -> Color {
+        let texels = footprint * self.width().max(self.height()) as f32;
+        let lod = texels.max(1.).log2().min((self.levels.len() - 1) as f32);
+
+        let (lo, t) = (lod.floor() as usize, lod.fract());
+        let color = self.sample_level(&self.levels[lo], uv);
+
+        if t > 0. && lo + 1 < self.levels.len() {
+            color * (1. - t) + self.sample_level(&self.levels[lo + 1], uv) * t
+        } else {
+            color
+        }
+    }
+
+    fn sample_level(&self, level: &Level, (u, v): (f32, f32)) -> Color {
+        // Texel centers lie at half-integer coordinates, and rows are stored from the top.
+        let x = u * level.width as f32;
+        let y = (1. - v) * level.height as f32;
+
+        let at = |x: i64, y: i64| {
+            let (x, y) = (
+                self.wrap.apply(x, level.width),
+                self.wrap.apply(y, level.height),
+            );
+            level.texels[y * level.width + x]
+        };
+
+        match self.filtering {
+            Filtering::Nearest => at(x.floor() as i64, y.floor() as i64),
+            Filtering::Bilinear => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                (at(x0, y0) * (1. - fx) + at(x0 + 1, y0) * fx) * (1. - fy)
+                    + (at(x0, y0 + 1) * (1. - fx) + at(x0 + 1, y0 + 1) * fx) * fy
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(l: f32) -> Color {
+        Color::from_rgb(l, l, l)
+    }
+
+    fn assert_gray(color: Color, l: f32) {
+        assert!((color.r - l).abs() <= 1e-5, "{color:?} != {l}");
+    }
+
+    ///
+    /// `size × size` checkerboard of black and white texels.
+    ///
+    fn checker(size: usize) -> ImageTexture {
+        let texels = (0..size * size)
+            .map(|i| gray(((i / size + i % size) % 2) as f32))
+            .collect();
+
+        ImageTexture::new(size, size, texels)
+    }
+
+    #[test]
+    fn wrap_test() {
+        let indices = |wrap: Wrap| (-5..9).map(|i| wrap.apply(i, 4)).collect::<Vec<_>>();
+
+        assert_eq!(
+            indices(Wrap::Repeat),
+            [3, 0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3, 0]
+        );
+        assert_eq!(
+            indices(Wrap::Clamp),
+            [0, 0, 0, 0, 0, 0, 1, 2, 3, 3, 3, 3, 3, 3]
+        );
+        assert_eq!(
+            indices(Wrap::Mirror),
+            [3, 3, 2, 1, 0, 0, 1, 2, 3, 3, 2, 1, 0, 0]
+        );
+    }
+
+    #[test]
+    fn nearest_test() {
+        // Bottom row is black then white, top row is white then black.
+        let texture = checker(2).with_filtering(Filtering::Nearest);
+
+        assert_gray(texture.sample((0.1, 0.9), 0.), 0.);
+        assert_gray(texture.sample((0.9, 0.9), 0.), 1.);
+        assert_gray(texture.sample((0.1, 0.1), 0.), 1.);
+        assert_gray(texture.sample((0.9, 0.1), 0.), 0.);
+
+        // Coordinates out of `[0, 1]` are wrapped.
+        assert_gray(texture.sample((1.1, 0.9), 0.), 0.);
+        assert_gray(texture.with_wrap(Wrap::Clamp).sample((1.1, 0.9), 0.), 1.);
+    }
+
+    #[test]
+    fn bilinear_test() {
+        let texture = ImageTexture::new(2, 1, vec![gray(0.), gray(1.)]).with_wrap(Wrap::Clamp);
+
+        // Texel centers are exact, and colors are interpolated in between.
+        assert_gray(texture.sample((0.25, 0.5), 0.), 0.);
+        assert_gray(texture.sample((0.75, 0.5), 0.), 1.);
+        assert_gray(texture.sample((0.5, 0.5), 0.), 0.5);
+        assert_gray(texture.sample((0.375, 0.5), 0.), 0.25);
+
+        // Past the centers of the border texels, they are extended...
+        assert_gray(texture.sample((0., 0.5), 0.), 0.);
+        assert_gray(texture.sample((1., 0.5), 0.), 1.);
+
+        // ...or blended with the opposite border when repeating.
+        let texture = texture.with_wrap(Wrap::Repeat);
+        assert_gray(texture.sample((0., 0.5), 0.), 0.5);
+    }
+
+    #[test]
+    fn mipmaps_test() {
+        let texture = checker(4).with_filtering(Filtering::Nearest);
+        let mipmapped = texture.clone().with_mipmaps();
+
+        let sizes = mipmapped
+            .levels
+            .iter()
+            .map(|l| (l.width, l.height))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [(4, 4), (2, 2), (1, 1)]);
+
+        // Without mipmaps, the footprint is ignored.
+        assert_gray(texture.sample((0.1, 0.9), 1.), 0.);
+
+        // A footprint of one texel or less reads the full resolution image.
+        assert_gray(mipmapped.sample((0.1, 0.9), 0.), 0.);
+        assert_gray(mipmapped.sample((0.1, 0.9), 0.25), 0.);
+
+        // Larger footprints read the averaged levels, the coarsest one at most.
+        assert_gray(mipmapped.sample((0.1, 0.9), 0.5), 0.5);
+        assert_gray(mipmapped.sample((0.1, 0.9), 10.), 0.5);
+
+        // In between, adjacent levels are blended.
+        let footprint = 2_f32.sqrt() / 4.;
+        assert_gray(mipmapped.sample((0.1, 0.9), footprint), 0.25);
+    }
+
+    #[test]
+    #[should_panic(expected = "ImageTexture::new: width must be a positive integer.")]
+    fn empty_test() {
+        ImageTexture::new(0, 1, vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "ImageTexture::new: expected width × height texels.")]
+    fn texels_test() {
+        ImageTexture::new(2, 2, vec![Color::WHITE; 3]);
+    }
+}
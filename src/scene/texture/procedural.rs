@@ -0,0 +1,253 @@
+//!
+//! Procedural textures.
+//!
+//! A [Procedural] texture computes its color from a mathematical [Pattern], instead of reading
+//! it from an image. Patterns have infinite resolution, and can be evaluated either on the
+//! surface texture coordinates, or directly in 3D space: solid textures such as marble or wood
+//! then look carved out of a block of material.
+//!
+//! Each pattern gives a value in `[0, 1]`, which blends between two colors.
+//!
+
+use std::f32::consts::PI;
+
+use crate::{
+    colors::Color,
+    maths::{noise, Point},
+    render::Intersection,
+};
+
+///
+/// Space in which a [Procedural] texture is evaluated.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Space {
+    /// Surface texture coordinates, as the point `(u, v, 0)`.
+    Uv,
+    /// World space position of the hit point.
+    World,
+}
+
+///
+/// Procedural pattern.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    /// Alternating cells, of unit size.
+    Checker,
+    /// Alternating bands along the first axis, of unit width.
+    Stripes,
+    /// Fractal Brownian motion of Perlin noise.
+    Noise { octaves: u32 },
+    /// Turbulence, i.e. fBm of the absolute value of Perlin noise.
+    Turbulence { octaves: u32 },
+    /// Veins along the first axis, distorted by turbulence of the given `strength`.
+    Marble { octaves: u32, strength: f32 },
+    /// Rings around the second axis, of unit spacing, distorted by noise of the given `strength`.
+    Wood { strength: f32 },
+}
+
+impl Pattern {
+    ///
+    /// Evaluate the pattern at `p`, giving a value in `[0, 1]`.
+    ///
+    pub fn eval(&self, p: Point) -> f32 {
+        match *self {
+            Self::Checker => {
+                let sum = p.x.floor() + p.y.floor() + p.z.floor();
+                sum.rem_euclid(2.)
+            }
+            Self::Stripes => p.x.floor().rem_euclid(2.),
+            Self::Noise { octaves } => (0.5 + 0.5 * noise::fbm(p, octaves)).clamp(0., 1.),
+            Self::Turbulence { octaves } => noise::turbulence(p, octaves).clamp(0., 1.),
+            Self::Marble { octaves, strength } => {
+                let phase = p.x + strength * noise::turbulence(p, octaves);
+                0.5 + 0.5 * (phase * PI).sin()
+            }
+            Self::Wood { strength } => {
+                let r = (p.x * p.x + p.z * p.z).sqrt() + strength * noise::fbm(p, 3);
+                r.rem_euclid(1.)
+            }
+        }
+    }
+}
+
+///
+/// Texture computed from a [Pattern].
+///
+/// See [module documentation](self) for more informations.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Procedural {
+    pattern: Pattern,
+    /// Color where the pattern is `0`.
+    low: Color,
+    /// Color where the pattern is `1`.
+    high: Color,
+    /// Number of pattern units per unit of texture space.
+    scale: f32,
+    space: Space,
+}
+
+impl Procedural {
+    ///
+    /// Create a new procedural texture, blending from `low` to `high` following the `pattern`.
+    ///
+    /// Surface patterns (checker and stripes) are evaluated on texture coordinates, and solid
+    /// patterns in world space.
+    ///
+    pub fn new(pattern: Pattern, low: Color, high: Color, scale: f32) -> Self {
+        let space = match pattern {
+            Pattern::Checker | Pattern::Stripes => Space::Uv,
+            _ => Space::World,
+        };
+
+        Self {
+            pattern,
+            low,
+            high,
+            scale,
+            space,
+        }
+    }
+
+    pub fn checker(low: Color, high: Color, scale: f32) -> Self {
+        Self::new(Pattern::Checker, low, high, scale)
+    }
+
+    pub fn stripes(low: Color, high: Color, scale: f32) -> Self {
+        Self::new(Pattern::Stripes, low, high, scale)
+    }
+
+    pub fn noise(low: Color, high: Color, scale: f32) -> Self {
+        Self::new(Pattern::Noise { octaves: 4 }, low, high, scale)
+    }
+
+    pub fn turbulence(low: Color, high: Color, scale: f32) -> Self {
+        Self::new(Pattern::Turbulence { octaves: 6 }, low, high, scale)
+    }
+
+    pub fn marble(low: Color, high: Color, scale: f32) -> Self {
+        let pattern = Pattern::Marble {
+            octaves: 6,
+            strength: 5.,
+        };
+
+        Self::new(pattern, low, high, scale)
+    }
+
+    pub fn wood(low: Color, high: Color, scale: f32) -> Self {
+        Self::new(Pattern::Wood { strength: 0.3 }, low, high, scale)
+    }
+
+    ///
+    /// Set the [Space] in which the pattern is evaluated.
+    ///
+    pub fn with_space(mut self, space: Space) -> Self {
+        self.space = space;
+        self
+    }
+
+    ///
+    /// Return the color of the texture at an [Intersection].
+    ///
+    pub fn eval(&self, inter: &Intersection) -> Color {
+        let p = match self.space {
+            Space::Uv => Point::new(inter.uv.0, inter.uv.1, 0.),
+            Space::World => inter.pos,
+        };
+        let p = Point::new(p.x * self.scale, p.y * self.scale, p.z * self.scale);
+        let t = self.pattern.eval(p);
+
+        self.low * (1. - t) + self.high * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maths::Vector;
+
+    #[test]
+    fn checker_test() {
+        let at = |x, y, z| Pattern::Checker.eval(Point::new(x, y, z));
+
+        // Adjacent cells alternate along each axis, including across the origin.
+        assert_eq!(at(0.5, 0.5, 0.5), 0.);
+        assert_eq!(at(1.5, 0.5, 0.5), 1.);
+        assert_eq!(at(0.5, 1.5, 0.5), 1.);
+        assert_eq!(at(0.5, 0.5, 1.5), 1.);
+        assert_eq!(at(-0.5, 0.5, 0.5), 1.);
+
+        // Diagonal cells match.
+        assert_eq!(at(1.5, 1.5, 0.5), 0.);
+        assert_eq!(at(-0.5, -0.5, 0.5), 0.);
+        assert_eq!(at(-2.5, 3.5, -1.5), 0.);
+    }
+
+    #[test]
+    fn stripes_test() {
+        let at = |x, y| Pattern::Stripes.eval(Point::new(x, y, 0.));
+
+        // Bands are a unit wide, so the pattern repeats every 2 units along the first axis only.
+        for x in [-3.25, -0.5, 0.25, 0.75, 1.5, 4.1] {
+            assert_eq!(at(x, 0.), at(x + 2., 0.));
+            assert_ne!(at(x, 0.), at(x + 1., 0.));
+            assert_eq!(at(x, 0.), at(x, 7.3));
+        }
+    }
+
+    #[test]
+    fn range_test() {
+        let patterns = [
+            Pattern::Noise { octaves: 4 },
+            Pattern::Turbulence { octaves: 6 },
+            Pattern::Marble {
+                octaves: 6,
+                strength: 5.,
+            },
+            Pattern::Wood { strength: 0.3 },
+        ];
+
+        for pattern in patterns {
+            for i in 0..100 {
+                let p = Point::new(i as f32 * 0.37, i as f32 * -0.71, i as f32 * 0.13);
+                let t = pattern.eval(p);
+                assert!((0. ..=1.).contains(&t), "{pattern:?} at {p:?} gives {t}");
+            }
+        }
+    }
+
+    #[test]
+    fn procedural_test() {
+        let (low, high) = (Color::BLACK, Color::WHITE);
+        let inter =
+            |pos, uv| Intersection::new(1., pos, Vector::Y).with_uv(uv, Vector::X, Vector::Z);
+
+        // Surface patterns follow the texture coordinates, not the position.
+        let checker = Procedural::checker(low, high, 2.);
+        assert_eq!(checker.eval(&inter(Point::ORIGIN, (0.25, 0.25))), low);
+        assert_eq!(checker.eval(&inter(Point::ORIGIN, (0.75, 0.25))), high);
+        assert_eq!(
+            checker.eval(&inter(Point::new(0.75, 0., 0.), (0.25, 0.25))),
+            low
+        );
+
+        // Unless evaluated in world space.
+        let checker = checker.with_space(Space::World);
+        assert_eq!(
+            checker.eval(&inter(Point::new(0.75, 0., 0.), (0.25, 0.25))),
+            high
+        );
+
+        // Solid patterns blend the two colors.
+        let marble = Procedural::marble(low, high, 1.);
+        let p = Point::new(0.3, 0.2, 0.1);
+        let t = Pattern::Marble {
+            octaves: 6,
+            strength: 5.,
+        }
+        .eval(p);
+        assert_eq!(marble.eval(&inter(p, (0., 0.))), low * (1. - t) + high * t);
+    }
+}